@@ -9,7 +9,7 @@ echo "Killing existing jlessbar..."
 echo "Launching kitty with jlessbar..."
 kitty \
   --class jlessbar \
-  -e $(which jlessbar) &
+  -e $(which jlessbar) ${1:+--monitor "$1"} &
 
 KITTY_PID=$!
 echo "Kitty PID: $KITTY_PID"
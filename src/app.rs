@@ -1,12 +1,22 @@
 use chrono::Local;
+use std::collections::HashMap;
 use std::error::Error;
-use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
-use crate::hyprland::{self, HyprlandClient};
+use crate::config::{Config, FullscreenConfig, FullscreenMode};
+use crate::hyprland::HyprlandClient;
 use crate::hyprland_ipc::{HyprlandEvent, HyprlandIPC};
 use crate::system::SystemInfo;
 
+// One fullscreen query's answer; queries can finish out of order, so each
+// carries the generation it was sent with
+#[derive(Debug)]
+pub struct FullscreenState {
+    generation: u64,
+    monitor: String,
+    is_full: bool,
+}
+
 pub struct App {
     pub curr_time: String,
     pub cpu_usage: f64,
@@ -16,19 +26,34 @@ pub struct App {
     pub network_name: String,
     pub network_connected: bool,
 
-    pub active_workspace: u32,
+    pub active_workspace: i32,
     pub window_title: String,
 
     pub battery_level: u8,
     pub battery_charging: bool,
+
+    pub focused_monitor: String,
+    // bar's own monitor; None follows whichever monitor has focus
+    pub bar_monitor: Option<String>,
+    fullscreen_monitors: HashMap<String, bool>,
+    // answers to queries run off the event loop
+    fullscreen_tx: mpsc::UnboundedSender<FullscreenState>,
+    fullscreen_rx: Option<mpsc::UnboundedReceiver<FullscreenState>>,
+    // generation of the last query sent and the newest answer applied
+    fullscreen_requested: u64,
+    fullscreen_applied: u64,
+    fullscreen_config: FullscreenConfig,
+    hidden: bool,
+
     system_info: SystemInfo,
     hyprland: Option<HyprlandClient>,
     event_rx: Option<mpsc::UnboundedReceiver<HyprlandEvent>>,
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(config: &Config) -> Self {
         let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (fullscreen_tx, fullscreen_rx) = mpsc::unbounded_channel();
         let hyprland = HyprlandClient::new().ok();
         if let Ok(ipc) = HyprlandIPC::new() {
             tokio::spawn(async move {
@@ -40,7 +65,7 @@ impl App {
             });
         }
 
-        Self {
+        let mut app = Self {
             curr_time: String::new(),
             cpu_usage: 0.0,
             disk_usage: 0.0,
@@ -51,10 +76,26 @@ impl App {
             window_title: String::new(),
             battery_level: 0,
             battery_charging: false,
+            focused_monitor: hyprland
+                .as_ref()
+                .and_then(|h| h.get_focused_monitor().ok())
+                .unwrap_or_default(),
+            bar_monitor: config.monitor.clone(),
+            fullscreen_monitors: HashMap::new(),
+            fullscreen_tx,
+            fullscreen_rx: Some(fullscreen_rx),
+            fullscreen_requested: 0,
+            fullscreen_applied: 0,
+            fullscreen_config: config.fullscreen.clone(),
+            hidden: false,
             system_info: SystemInfo::new(),
             event_rx: Some(event_rx),
             hyprland,
-        }
+        };
+
+        // a bar started over a fullscreen window has no event to wait for
+        app.sync_fullscreen(None);
+        app
     }
 
     pub fn update(&mut self) -> Result<(), Box<dyn Error>> {
@@ -73,38 +114,129 @@ impl App {
         self.event_rx.take().expect("Event reciever already taken")
     }
 
+    pub fn take_fullscreen_receiver(&mut self) -> mpsc::UnboundedReceiver<FullscreenState> {
+        self.fullscreen_rx
+            .take()
+            .expect("Fullscreen receiver already taken")
+    }
+
     pub fn process_event(&mut self, event: HyprlandEvent) {
         match event {
             HyprlandEvent::WorkspaceChanged(id) => {
                 self.active_workspace = id;
+                self.sync_fullscreen(None);
             }
-            HyprlandEvent::ActiveWindowChanged(title) => {
-                self.window_title = title;
+            HyprlandEvent::ActiveWindowChanged(window) => {
+                self.window_title = window.title;
             }
             HyprlandEvent::Fullscreen(is_full) => {
-                //TODO hide bar
+                self.sync_fullscreen(Some(is_full));
             }
-            HyprlandEvent::MonitorFocused(_) => {}
-            _ => {}
+            HyprlandEvent::MonitorFocused(monitor, _) => {
+                self.focused_monitor = monitor;
+                self.sync_fullscreen(None);
+            }
+            HyprlandEvent::SpecialWorkspaceChanged(..)
+            | HyprlandEvent::Urgent(_)
+            | HyprlandEvent::WindowsChanged => {}
         }
     }
 
     fn process_events(&mut self) {
+        let mut events = Vec::new();
         if let Some(ref mut rx) = self.event_rx {
             while let Ok(event) = rx.try_recv() {
-                match event {
-                    HyprlandEvent::WorkspaceChanged(id) => {
-                        self.active_workspace = id;
-                    }
-                    HyprlandEvent::ActiveWindowChanged(title) => {
-                        self.window_title = title;
-                    }
-                    HyprlandEvent::Fullscreen(is_full) => {
-                        //TODO hide bar
-                    }
-                    HyprlandEvent::MonitorFocused(_) => {}
-                    _ => {}
-                }
+                events.push(event);
+            }
+        }
+        for event in events {
+            self.process_event(event);
+        }
+    }
+
+    // Hyprland's fullscreen event doesn't say which monitor it's for, so ask
+    // for the focused workspace and fall back to the event's own value. The
+    // hyprctl call blocks, so it runs off the event loop and the answer
+    // comes back through take_fullscreen_receiver
+    fn sync_fullscreen(&mut self, event_state: Option<bool>) {
+        self.fullscreen_requested += 1;
+        let generation = self.fullscreen_requested;
+        let focused = self.focused_monitor.clone();
+        let Some(hyprland) = self.hyprland.clone() else {
+            if let Some(is_full) = event_state {
+                let _ = self.fullscreen_tx.send(FullscreenState {
+                    generation,
+                    monitor: focused,
+                    is_full,
+                });
+            }
+            return;
+        };
+        let tx = self.fullscreen_tx.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let queried = hyprland.get_fullscreen_state().ok();
+            let (monitor, is_full) = match (queried, event_state) {
+                (Some((monitor, is_full)), _) if !monitor.is_empty() => (monitor, is_full),
+                (_, Some(is_full)) => (focused, is_full),
+                _ => return,
+            };
+            let _ = tx.send(FullscreenState {
+                generation,
+                monitor,
+                is_full,
+            });
+        });
+    }
+
+    // an answer older than one already applied is stale and dropped
+    pub fn set_fullscreen(&mut self, state: FullscreenState) {
+        if state.generation <= self.fullscreen_applied {
+            return;
+        }
+        self.fullscreen_applied = state.generation;
+        self.fullscreen_monitors
+            .insert(state.monitor, state.is_full);
+        self.apply_fullscreen();
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        let monitor = self.bar_monitor.as_ref().unwrap_or(&self.focused_monitor);
+        self.fullscreen_monitors
+            .get(monitor)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    pub fn fullscreen_mode(&self) -> Option<&FullscreenMode> {
+        if self.hidden {
+            Some(&self.fullscreen_config.mode)
+        } else {
+            None
+        }
+    }
+
+    pub fn minimal_module(&self) -> &str {
+        &self.fullscreen_config.minimal_module
+    }
+
+    fn apply_fullscreen(&mut self) {
+        let hidden = self.is_fullscreen() && self.fullscreen_config.mode != FullscreenMode::Ignore;
+        if hidden == self.hidden {
+            return;
+        }
+        self.hidden = hidden;
+
+        if self.fullscreen_config.mode == FullscreenMode::Dispatch {
+            let args = if hidden {
+                &self.fullscreen_config.hide_dispatch
+            } else {
+                &self.fullscreen_config.show_dispatch
+            };
+            if let Some(hyprland) = &self.hyprland
+                && let Err(e) = hyprland.dispatch(args)
+            {
+                eprintln!("Fullscreen dispatch failed: {}", e);
             }
         }
     }
@@ -126,3 +258,33 @@ impl App {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(generation: u64, is_full: bool) -> FullscreenState {
+        FullscreenState {
+            generation,
+            monitor: "DP-1".to_string(),
+            is_full,
+        }
+    }
+
+    #[test]
+    fn stale_answers_are_dropped() {
+        let config = Config {
+            monitor: Some("DP-1".to_string()),
+            ..Config::default()
+        };
+        let mut app = App::new(&config);
+
+        // the second query answers first
+        app.set_fullscreen(state(2, true));
+        app.set_fullscreen(state(1, false));
+        assert!(app.is_fullscreen());
+
+        app.set_fullscreen(state(3, false));
+        assert!(!app.is_fullscreen());
+    }
+}
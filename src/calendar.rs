@@ -0,0 +1,164 @@
+// Month calendar the clock opens in its own terminal window (`--calendar`)
+use chrono::{Datelike, Duration, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
+use crossterm::{
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, MouseEventKind},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Frame, Terminal,
+    backend::CrosstermBackend,
+    layout::Alignment,
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+use std::error::Error;
+use std::io;
+
+use crate::styles::*;
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+// today in the clock's timezone, so the highlight matches what the bar shows
+fn today(timezone: Option<&str>) -> NaiveDate {
+    match timezone.and_then(|name| name.parse::<Tz>().ok()) {
+        Some(tz) => Utc::now().with_timezone(&tz).date_naive(),
+        None => Local::now().date_naive(),
+    }
+}
+
+// months since year 0, so moving between months is plain arithmetic
+fn month_index(date: NaiveDate) -> i32 {
+    date.year() * 12 + date.month0() as i32
+}
+
+fn first_of_month(index: i32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(index.div_euclid(12), index.rem_euclid(12) as u32 + 1, 1)
+}
+
+fn month_lines(index: i32, today: NaiveDate) -> Vec<Line<'static>> {
+    let Some(first) = first_of_month(index) else {
+        return Vec::new();
+    };
+    let offset = first.weekday().num_days_from_monday() as i64;
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("{} {}", MONTHS[first.month0() as usize], first.year()),
+            clock_style(),
+        )),
+        Line::from(Span::styled(
+            " Wk  Mo Tu We Th Fr Sa Su",
+            calendar_header_style(),
+        )),
+    ];
+
+    // every day of a Monday-to-Sunday row shares the Monday's ISO week
+    let mut monday = first - Duration::days(offset);
+    while month_index(monday) <= index {
+        let mut spans = vec![Span::styled(
+            format!(" {:>2} ", monday.iso_week().week()),
+            calendar_week_style(),
+        )];
+
+        for day in 0..7 {
+            let date = monday + Duration::days(day);
+            if month_index(date) != index {
+                spans.push(Span::raw("   "));
+                continue;
+            }
+
+            spans.push(Span::raw(" "));
+            let style = if date == today {
+                calendar_today_style()
+            } else {
+                Style::default().fg(TEXT)
+            };
+            spans.push(Span::styled(format!("{:>2}", date.day()), style));
+        }
+
+        lines.push(Line::from(spans));
+        monday += Duration::days(7);
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::from(Span::styled(
+        "←/→ month  t today  q close",
+        calendar_header_style(),
+    )));
+    lines
+}
+
+fn draw(f: &mut Frame, index: i32, today: NaiveDate) {
+    let area = f.size();
+    let lines = month_lines(index, today);
+
+    // centred vertically as well, since the window is usually a bit taller
+    let mut area = area;
+    let height = lines.len() as u16;
+    area.y += area.height.saturating_sub(height) / 2;
+    area.height = area.height.min(height);
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().bg(SURFACE));
+    f.render_widget(paragraph, area);
+}
+
+pub fn run(timezone: Option<&str>) -> Result<(), Box<dyn Error>> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let today = today(timezone);
+    let mut index = month_index(today);
+
+    loop {
+        terminal.draw(|f| draw(f, index, today))?;
+
+        match event::read()? {
+            Event::Key(key) => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Left | KeyCode::Up | KeyCode::PageUp | KeyCode::Char('h' | 'k') => {
+                    index -= 1
+                }
+                KeyCode::Right | KeyCode::Down | KeyCode::PageDown | KeyCode::Char('l' | 'j') => {
+                    index += 1
+                }
+                KeyCode::Home | KeyCode::Char('t') => index = month_index(today),
+                _ => {}
+            },
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::ScrollUp => index -= 1,
+                MouseEventKind::ScrollDown => index += 1,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    Ok(())
+}
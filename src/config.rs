@@ -10,6 +10,11 @@ pub struct Config {
     pub modules: Vec<String>,
     pub module_configs: HashMap<String, ModuleConfig>,
     pub colors: ColorConfig,
+    #[serde(default)]
+    pub fullscreen: FullscreenConfig,
+    // monitor the bar lives on; overridden by --monitor
+    #[serde(default)]
+    pub monitor: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -19,6 +24,36 @@ pub struct ColorConfig {
     pub text: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum FullscreenMode {
+    Ignore,
+    Hide,
+    Minimal,
+    Dispatch,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FullscreenConfig {
+    pub mode: FullscreenMode,
+    // module kept on screen in Minimal mode
+    pub minimal_module: String,
+    // hyprctl dispatch args sent when fullscreen starts / ends in Dispatch mode
+    pub hide_dispatch: String,
+    pub show_dispatch: String,
+}
+
+impl Default for FullscreenConfig {
+    fn default() -> Self {
+        Self {
+            mode: FullscreenMode::Hide,
+            minimal_module: "clock".to_string(),
+            hide_dispatch: "movetoworkspacesilent special:jlessbar,class:^(jlessbar)$".to_string(),
+            show_dispatch: "movetoworkspacesilent e+0,class:^(jlessbar)$".to_string(),
+        }
+    }
+}
+
 impl Config {
     pub fn load() -> Result<Self, Box<dyn Error>> {
         let config_path = Self::config_path()?;
@@ -66,6 +101,18 @@ impl Default for Config {
                 format: Some("{id}".to_string()),
                 interval: None,
                 position: ModulePosition::Left,
+                options: HashMap::new(),
+            },
+        );
+
+        module_configs.insert(
+            "monitor".to_string(),
+            ModuleConfig {
+                enabled: true,
+                format: Some("{icon} {name}".to_string()),
+                interval: None,
+                position: ModulePosition::Left,
+                options: HashMap::new(),
             },
         );
 
@@ -76,6 +123,7 @@ impl Default for Config {
                 format: Some("{title}".to_string()),
                 interval: None,
                 position: ModulePosition::CenterLeft,
+                options: HashMap::new(),
             },
         );
 
@@ -86,6 +134,7 @@ impl Default for Config {
                 format: Some("%H:%M:%S".to_string()),
                 interval: Some(1000),
                 position: ModulePosition::CenterRight,
+                options: HashMap::new(),
             },
         );
 
@@ -96,6 +145,7 @@ impl Default for Config {
                 format: Some("{icon} {usage}%".to_string()),
                 interval: Some(2000),
                 position: ModulePosition::Right,
+                options: HashMap::new(),
             },
         );
 
@@ -106,6 +156,40 @@ impl Default for Config {
                 format: Some("{icon} {usage}%".to_string()),
                 interval: Some(2000),
                 position: ModulePosition::Right,
+                options: HashMap::new(),
+            },
+        );
+
+        module_configs.insert(
+            "disk".to_string(),
+            ModuleConfig {
+                enabled: true,
+                format: Some("{icon} {percentage}%".to_string()),
+                interval: Some(30000),
+                position: ModulePosition::Right,
+                options: HashMap::new(),
+            },
+        );
+
+        module_configs.insert(
+            "diskio".to_string(),
+            ModuleConfig {
+                enabled: true,
+                format: Some("{icon} {read} {write}".to_string()),
+                interval: Some(2000),
+                position: ModulePosition::Right,
+                options: HashMap::new(),
+            },
+        );
+
+        module_configs.insert(
+            "temperature".to_string(),
+            ModuleConfig {
+                enabled: true,
+                format: Some("{icon} {temp}{unit}".to_string()),
+                interval: Some(2000),
+                position: ModulePosition::Right,
+                options: HashMap::new(),
             },
         );
 
@@ -116,6 +200,7 @@ impl Default for Config {
                 format: Some("{icon} {ssid}".to_string()),
                 interval: Some(2000),
                 position: ModulePosition::Right,
+                options: HashMap::new(),
             },
         );
 
@@ -126,16 +211,21 @@ impl Default for Config {
                 format: Some("{icon} {level}%".to_string()),
                 interval: Some(30000),
                 position: ModulePosition::Right,
+                options: HashMap::new(),
             },
         );
 
         Self {
             modules: vec![
                 "workspaces".to_string(),
+                "monitor".to_string(),
                 "window".to_string(),
                 "clock".to_string(),
                 "cpu".to_string(),
                 "memory".to_string(),
+                "disk".to_string(),
+                "diskio".to_string(),
+                "temperature".to_string(),
                 "network".to_string(),
                 "battery".to_string(),
             ],
@@ -145,6 +235,8 @@ impl Default for Config {
                 surface: "#16121B".to_string(),
                 text: "E9DFEE".to_string(),
             },
+            fullscreen: FullscreenConfig::default(),
+            monitor: None,
         }
     }
 }
@@ -4,8 +4,12 @@ use std::process::Command;
 
 #[derive(Debug, Deserialize, Serialize)]
 struct Workspace {
-    id: u32,
+    id: i32,
     name: String,
+    #[serde(default)]
+    monitor: String,
+    #[serde(default)]
+    hasfullscreen: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -14,6 +18,22 @@ struct Window {
     title: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WorkspaceRef {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Monitor {
+    pub name: String,
+    #[serde(default)]
+    pub focused: bool,
+    #[serde(rename = "activeWorkspace")]
+    pub active_workspace: WorkspaceRef,
+}
+
+#[derive(Clone)]
 pub struct HyprlandClient {
     instance_signature: String,
 }
@@ -28,7 +48,7 @@ impl HyprlandClient {
         })
     }
 
-    pub fn get_active_workspace(&self) -> Result<u32, Box<dyn Error>> {
+    pub fn get_active_workspace(&self) -> Result<i32, Box<dyn Error>> {
         let output = Command::new("hyprctl")
             .args(["activeworkspace", "-j"])
             .output()?;
@@ -37,6 +57,39 @@ impl HyprlandClient {
         Ok(workspace.id)
     }
 
+    // (monitor name, has fullscreen window) for the focused workspace
+    pub fn get_fullscreen_state(&self) -> Result<(String, bool), Box<dyn Error>> {
+        let output = Command::new("hyprctl")
+            .args(["activeworkspace", "-j"])
+            .output()?;
+
+        let workspace: Workspace = serde_json::from_slice(&output.stdout)?;
+        Ok((workspace.monitor, workspace.hasfullscreen))
+    }
+
+    pub fn get_monitors(&self) -> Result<Vec<Monitor>, Box<dyn Error>> {
+        let output = Command::new("hyprctl").args(["monitors", "-j"]).output()?;
+
+        let monitors: Vec<Monitor> = serde_json::from_slice(&output.stdout)?;
+        Ok(monitors)
+    }
+
+    pub fn get_focused_monitor(&self) -> Result<String, Box<dyn Error>> {
+        self.get_monitors()?
+            .into_iter()
+            .find(|m| m.focused)
+            .map(|m| m.name)
+            .ok_or_else(|| "No focused monitor".into())
+    }
+
+    pub fn dispatch(&self, args: &str) -> Result<(), Box<dyn Error>> {
+        Command::new("hyprctl")
+            .arg("dispatch")
+            .args(args.split_whitespace())
+            .spawn()?;
+        Ok(())
+    }
+
     pub fn get_active_window(&self) -> Result<String, Box<dyn Error>> {
         let output = Command::new("hyprctl")
             .args(["activewindow", "-j"])
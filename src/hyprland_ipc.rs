@@ -4,11 +4,26 @@ use std::path::PathBuf;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::net::UnixStream;
 
+#[derive(Debug, Clone, Default)]
+pub struct ActiveWindow {
+    pub class: String,
+    pub title: String,
+    // hex without the 0x prefix; empty when nothing has focus
+    pub address: String,
+}
+
 #[derive(Clone)]
 pub enum HyprlandEvent {
-    WorkspaceChanged(u32),
-    ActiveWindowChanged(String),
-    MonitorFocused(String),
+    WorkspaceChanged(i32),
+    // (special workspace name, monitor); name is empty once it's closed
+    SpecialWorkspaceChanged(String, String),
+    ActiveWindowChanged(ActiveWindow),
+    // (monitor, name of its active workspace)
+    MonitorFocused(String, String),
+    // window address that asked for attention
+    Urgent(String),
+    // a window opened, closed or moved to another workspace
+    WindowsChanged,
     Fullscreen(bool),
 }
 
@@ -35,16 +50,24 @@ impl HyprlandIPC {
         let stream = UnixStream::connect(&self.socket_path).await?;
         let reader = BufReader::new(stream);
         let mut lines = reader.lines();
+        let mut pending_window = None;
 
         while let Some(line) = lines.next_line().await? {
-            if let Some(event) = self.parse_event(&line) {
+            if let Some(event) = self.parse_event(&line, &mut pending_window) {
                 callback(event);
             }
         }
 
         Ok(())
     }
-    fn parse_event(&self, line: &str) -> Option<HyprlandEvent> {
+
+    // activewindow carries class and title, the activewindowv2 right after it
+    // carries the address, so the first is held until the second arrives
+    fn parse_event(
+        &self,
+        line: &str,
+        pending_window: &mut Option<ActiveWindow>,
+    ) -> Option<HyprlandEvent> {
         let parts: Vec<&str> = line.splitn(2, ">>").collect();
         if parts.len() != 2 {
             return None;
@@ -53,21 +76,43 @@ impl HyprlandIPC {
         let event_type = parts[0];
         let data = parts[1];
         match event_type {
-            "workspace" => {
-                if let Ok(workspace_id) = data.parse::<u32>() {
-                    Some(HyprlandEvent::WorkspaceChanged(workspace_id))
-                } else {
-                    None
-                }
+            // v1 only carries the name, so named workspaces need the id from
+            // v2; both fire on every switch, so v1 is ignored
+            "workspacev2" => {
+                let id = data.split(',').next()?.parse::<i32>().ok()?;
+                Some(HyprlandEvent::WorkspaceChanged(id))
+            }
+            "activespecial" => {
+                let (name, monitor) = data.rsplit_once(',')?;
+                Some(HyprlandEvent::SpecialWorkspaceChanged(
+                    name.to_string(),
+                    monitor.to_string(),
+                ))
             }
             "activewindow" => {
-                let title = data.split(',').nth(1).unwrap_or("").to_string();
-                Some(HyprlandEvent::ActiveWindowChanged(title))
+                // only the first comma separates class from title
+                let (class, title) = data.split_once(',').unwrap_or((data, ""));
+                *pending_window = Some(ActiveWindow {
+                    class: class.to_string(),
+                    title: title.to_string(),
+                    address: String::new(),
+                });
+                None
+            }
+            "activewindowv2" => {
+                let mut window = pending_window.take().unwrap_or_default();
+                window.address = data.trim_start_matches("0x").to_string();
+                Some(HyprlandEvent::ActiveWindowChanged(window))
             }
             "focusedmon" => {
-                let monitor = data.split(',').next().unwrap_or("").to_string();
-                Some(HyprlandEvent::MonitorFocused(monitor))
+                let (monitor, workspace) = data.split_once(',').unwrap_or((data, ""));
+                Some(HyprlandEvent::MonitorFocused(
+                    monitor.to_string(),
+                    workspace.to_string(),
+                ))
             }
+            "urgent" => Some(HyprlandEvent::Urgent(data.to_string())),
+            "openwindow" | "closewindow" | "movewindowv2" => Some(HyprlandEvent::WindowsChanged),
             "fullscreen" => {
                 let is_fullscreen = data == "1";
                 Some(HyprlandEvent::Fullscreen(is_fullscreen))
@@ -76,3 +121,77 @@ impl HyprlandIPC {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ipc() -> HyprlandIPC {
+        HyprlandIPC {
+            socket_path: PathBuf::new(),
+        }
+    }
+
+    fn active_window(event: Option<HyprlandEvent>) -> ActiveWindow {
+        match event {
+            Some(HyprlandEvent::ActiveWindowChanged(window)) => window,
+            _ => panic!("expected an ActiveWindowChanged event"),
+        }
+    }
+
+    #[test]
+    fn title_keeps_its_commas() {
+        let ipc = ipc();
+        let mut pending = None;
+
+        assert!(
+            ipc.parse_event("activewindow>>firefox,a, b, c", &mut pending)
+                .is_none()
+        );
+        let window = active_window(ipc.parse_event("activewindowv2>>0xabc", &mut pending));
+
+        assert_eq!(window.class, "firefox");
+        assert_eq!(window.title, "a, b, c");
+        assert_eq!(window.address, "abc");
+        assert!(pending.is_none());
+    }
+
+    #[test]
+    fn nothing_focused() {
+        let ipc = ipc();
+        let mut pending = None;
+
+        assert!(ipc.parse_event("activewindow>>,", &mut pending).is_none());
+        let window = active_window(ipc.parse_event("activewindowv2>>", &mut pending));
+
+        assert_eq!(window.class, "");
+        assert_eq!(window.title, "");
+        assert_eq!(window.address, "");
+    }
+
+    #[test]
+    fn address_without_pending_window() {
+        let ipc = ipc();
+        let mut pending = None;
+
+        let window = active_window(ipc.parse_event("activewindowv2>>0x55d0", &mut pending));
+
+        assert_eq!(window.class, "");
+        assert_eq!(window.title, "");
+        assert_eq!(window.address, "55d0");
+    }
+
+    #[test]
+    fn focused_monitor_brings_its_workspace() {
+        let ipc = ipc();
+        let mut pending = None;
+
+        match ipc.parse_event("focusedmon>>DP-2,web, mail", &mut pending) {
+            Some(HyprlandEvent::MonitorFocused(monitor, workspace)) => {
+                assert_eq!(monitor, "DP-2");
+                assert_eq!(workspace, "web, mail");
+            }
+            _ => panic!("expected a MonitorFocused event"),
+        }
+    }
+}
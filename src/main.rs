@@ -1,5 +1,7 @@
 use crossterm::{
-    event::{self, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, MouseButton, MouseEventKind,
+    },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
@@ -8,47 +10,90 @@ use std::io;
 use tokio::time::{Duration, interval};
 
 mod app;
+mod calendar;
 mod config;
 mod hyprland;
 mod hyprland_ipc;
+mod markup;
 mod module_manager;
 mod modules;
+mod net;
+mod notify;
 mod styles;
 mod system;
+mod tooltip;
 mod ui;
+mod units;
 use app::App;
 use module_manager::ModuleManager;
 use ui::render_ui;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // the clock opens this in a window of its own
+    if std::env::args().any(|arg| arg == "--calendar") {
+        return calendar::run(arg_value("--timezone").as_deref());
+    }
+    // so does a module's right click when the bar has no room for tooltips
+    if let Some(module) = arg_value("--tooltip") {
+        return tooltip::run(&config::Config::load()?, &module);
+    }
+
     //setup term
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let config = config::Config::load()?;
+    let mut config = config::Config::load()?;
+    if let Some(monitor) = arg_value("--monitor") {
+        config.monitor = Some(monitor);
+    }
 
     //app init
-    let mut app = App::new();
+    let mut app = App::new(&config);
     let mut module_manager = ModuleManager::new(&config);
 
     let mut tick_interval = interval(Duration::from_millis(100));
     let mut event_rx = app.take_event_reciever();
+    let mut fullscreen_rx = app.take_fullscreen_receiver();
 
-    loop {
+    'main: loop {
         //draw handle here VV
-        terminal.draw(|f| render_ui(f, &app, &module_manager))?;
+        let mut regions = Vec::new();
+        terminal.draw(|f| regions = render_ui(f, &app, &module_manager))?;
+        module_manager.set_regions(regions);
 
         //Handle events with timeout VV
-        if event::poll(Duration::from_millis(0))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => break,
-                    _ => {}
+        while event::poll(Duration::from_millis(0))? {
+            match event::read()? {
+                Event::Key(key) if key.code == KeyCode::Char('q') => break 'main,
+                Event::Mouse(mouse) => {
+                    // tooltips go under the bar when the window is tall enough
+                    let inline = terminal.size()?.height > 1;
+                    let result = match mouse.kind {
+                        MouseEventKind::Down(MouseButton::Left) => {
+                            module_manager.handle_click(mouse.column, mouse.row)
+                        }
+                        MouseEventKind::Down(MouseButton::Right) => {
+                            module_manager.handle_tooltip_click(mouse.column, inline)
+                        }
+                        MouseEventKind::Moved if inline => {
+                            module_manager.handle_hover(mouse.column);
+                            Ok(())
+                        }
+                        MouseEventKind::ScrollUp => module_manager.handle_scroll(mouse.column, 1),
+                        MouseEventKind::ScrollDown => {
+                            module_manager.handle_scroll(mouse.column, -1)
+                        }
+                        _ => Ok(()),
+                    };
+                    if let Err(e) = result {
+                        eprintln!("Mouse action failed: {}", e);
+                    }
                 }
+                _ => {}
             }
         }
         tokio::select! {
@@ -59,10 +104,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 app.process_event(hypr_event.clone());
                 module_manager.handle_hyprland_event(&hypr_event);
             }
+            Some(state) = fullscreen_rx.recv() => {
+                app.set_fullscreen(state);
+            }
         }
     }
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
 
     Ok(())
 }
+
+// --flag <value> / --flag=<value>
+fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
@@ -0,0 +1,376 @@
+// Inline styling in format strings: <span fg="red" weight="bold">…</span>
+// or the <b> <i> <u> <s> shorthands around any stretch of text, or
+// {placeholder:fg=#ff0000,bold} on one placeholder. ANSI escapes, in the
+// format or in substituted values such as a script's output, are kept too
+use ansi_to_tui::IntoText;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^<(/?)(span|b|i|u|s)(\s[^>]*)?>").unwrap());
+
+static SGR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\x1b\[([0-9;]*)m").unwrap());
+
+static SPAN_ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([\w-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap());
+
+static STYLED_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\{(\w+):([^{}]+)\}").unwrap());
+
+// names ("red", "lightblue"), "#rrggbb" or a 0-255 palette index
+fn parse_color(value: &str) -> Option<Color> {
+    value.trim().parse().ok()
+}
+
+fn apply(style: Style, key: &str, value: &str) -> Style {
+    let value = value.trim().to_lowercase();
+    match key.trim() {
+        "fg" | "foreground" | "color" => match parse_color(&value) {
+            Some(color) => style.fg(color),
+            None => style,
+        },
+        "bg" | "background" => match parse_color(&value) {
+            Some(color) => style.bg(color),
+            None => style,
+        },
+        "weight" => match value.as_str() {
+            "bold" | "heavy" | "ultrabold" => style.add_modifier(Modifier::BOLD),
+            "light" | "ultralight" => style.add_modifier(Modifier::DIM),
+            _ => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+        },
+        "style" => match value.as_str() {
+            "italic" | "oblique" => style.add_modifier(Modifier::ITALIC),
+            _ => style.remove_modifier(Modifier::ITALIC),
+        },
+        "underline" => match value.as_str() {
+            "none" | "false" => style.remove_modifier(Modifier::UNDERLINED),
+            _ => style.add_modifier(Modifier::UNDERLINED),
+        },
+        "strikethrough" => match value.as_str() {
+            "true" => style.add_modifier(Modifier::CROSSED_OUT),
+            _ => style.remove_modifier(Modifier::CROSSED_OUT),
+        },
+        _ => style,
+    }
+}
+
+// fg="red" weight='bold' underline=single
+fn span_style(style: Style, attributes: &str) -> Style {
+    SPAN_ATTRIBUTE
+        .captures_iter(attributes)
+        .fold(style, |style, caps| {
+            let value = caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .or_else(|| caps.get(4))
+                .map_or("", |m| m.as_str());
+            apply(style, &caps[1], value)
+        })
+}
+
+// fg=#ff0000,bg=black,bold,italic
+fn placeholder_style(style: Style, flags: &str) -> Style {
+    flags.split(',').fold(style, |style, flag| {
+        if let Some((key, value)) = flag.split_once('=') {
+            return apply(style, key, value);
+        }
+        match flag.trim() {
+            "bold" => style.add_modifier(Modifier::BOLD),
+            "dim" => style.add_modifier(Modifier::DIM),
+            "italic" => style.add_modifier(Modifier::ITALIC),
+            "underline" => style.add_modifier(Modifier::UNDERLINED),
+            "strikethrough" => style.add_modifier(Modifier::CROSSED_OUT),
+            "reverse" => style.add_modifier(Modifier::REVERSED),
+            _ => style,
+        }
+    })
+}
+
+fn tag_style(style: Style, tag: &str, attributes: &str) -> Style {
+    match tag {
+        "b" => style.add_modifier(Modifier::BOLD),
+        "i" => style.add_modifier(Modifier::ITALIC),
+        "u" => style.add_modifier(Modifier::UNDERLINED),
+        "s" => style.add_modifier(Modifier::CROSSED_OUT),
+        _ => span_style(style, attributes),
+    }
+}
+
+// &lt; and friends, for a literal < or & in the format
+fn entity(text: &str) -> Option<(char, usize)> {
+    [
+        ("&lt;", '<'),
+        ("&gt;", '>'),
+        ("&amp;", '&'),
+        ("&quot;", '"'),
+        ("&apos;", '\''),
+    ]
+    .into_iter()
+    .find_map(|(name, c)| text.starts_with(name).then_some((c, name.len())))
+}
+
+// Splits the format into runs of text sharing a style; a styled placeholder
+// becomes a run of its own, "{name}", so it is still substituted later
+fn parse(format: &str, base: Style) -> Vec<(String, Style)> {
+    let mut runs = Vec::new();
+    // open tags with the style each one set
+    let mut stack: Vec<(&str, Style)> = Vec::new();
+    let mut text = String::new();
+    let mut rest = format;
+
+    let flush = |text: &mut String, runs: &mut Vec<(String, Style)>, style: Style| {
+        if !text.is_empty() {
+            runs.push((std::mem::take(text), style));
+        }
+    };
+
+    while let Some(c) = rest.chars().next() {
+        let top = stack.last().map_or(base, |(_, style)| *style);
+
+        if c == '<'
+            && let Some(caps) = TAG.captures(rest)
+        {
+            flush(&mut text, &mut runs, top);
+            let tag = caps.get(2).map_or("", |m| m.as_str());
+            if !caps[1].is_empty() {
+                // ends the innermost open tag of its kind, and anything left
+                // open inside it; a stray one is ignored
+                if let Some(open) = stack.iter().rposition(|(name, _)| *name == tag) {
+                    stack.truncate(open);
+                }
+            } else {
+                let attributes = caps.get(3).map_or("", |m| m.as_str());
+                stack.push((tag, tag_style(top, tag, attributes)));
+            }
+            rest = &rest[caps[0].len()..];
+            continue;
+        }
+
+        if c == '&'
+            && let Some((decoded, len)) = entity(rest)
+        {
+            text.push(decoded);
+            rest = &rest[len..];
+            continue;
+        }
+
+        if c == '{'
+            && let Some(caps) = STYLED_PLACEHOLDER.captures(rest)
+        {
+            flush(&mut text, &mut runs, top);
+            runs.push((
+                format!("{{{}}}", &caps[1]),
+                placeholder_style(top, &caps[2]),
+            ));
+            rest = &rest[caps[0].len()..];
+            continue;
+        }
+
+        text.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    flush(
+        &mut text,
+        &mut runs,
+        stack.last().map_or(base, |(_, style)| *style),
+    );
+    runs
+}
+
+// ansi-to-tui 4 drops combined codes such as \e[1;34m, so each one gets a
+// sequence of its own; 38/48 keep their colour arguments with them
+fn split_sgr(text: &str) -> String {
+    SGR.replace_all(text, |caps: &regex::Captures| {
+        let mut params = caps[1].split(';').peekable();
+        let mut out = String::new();
+        while let Some(param) = params.next() {
+            let mut code = param.to_string();
+            if matches!(param, "38" | "48") {
+                let count = match params.peek() {
+                    Some(&"5") => 2,
+                    Some(&"2") => 4,
+                    _ => 0,
+                };
+                for arg in params.by_ref().take(count) {
+                    code.push(';');
+                    code.push_str(arg);
+                }
+            }
+            out.push_str(&format!("\x1b[{}m", code));
+        }
+        out
+    })
+    .into_owned()
+}
+
+// Spans for text that may carry ANSI escapes, laid over style. A reset
+// goes back to style rather than the terminal's default colours; escapes
+// with no text between them leave no empty spans behind
+fn ansi_spans(text: String, style: Style) -> Vec<Span<'static>> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    if !text.contains('\x1b') {
+        return vec![Span::styled(text, style)];
+    }
+    let Ok(parsed) = split_sgr(&text).into_text() else {
+        return vec![Span::styled(text, style)];
+    };
+
+    parsed
+        .lines
+        .into_iter()
+        .flat_map(|line| line.spans)
+        .filter(|span| !span.content.is_empty())
+        .map(|span| {
+            let mut overlay = Style::default().add_modifier(span.style.add_modifier);
+            overlay.fg = span.style.fg.filter(|c| *c != Color::Reset);
+            overlay.bg = span.style.bg.filter(|c| *c != Color::Reset);
+            Span::styled(span.content, style.patch(overlay))
+        })
+        .collect()
+}
+
+// Text that may carry ANSI escapes but is never read as markup
+pub fn plain(text: &str, base: Style) -> Line<'static> {
+    Line::from(ansi_spans(text.to_string(), base))
+}
+
+// The format's markup turned into spans on top of base, with substitute
+// filling in placeholders run by run so values are never read as markup
+pub fn render(format: &str, base: Style, substitute: impl Fn(&str) -> String) -> Line<'static> {
+    let spans: Vec<Span<'static>> = parse(format, base)
+        .into_iter()
+        .flat_map(|(text, style)| ansi_spans(substitute(&text), style))
+        .collect();
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(format: &str) -> Vec<(String, Style)> {
+        render(format, Style::default(), |text| text.to_string())
+            .spans
+            .into_iter()
+            .map(|span| (span.content.into_owned(), span.style))
+            .collect()
+    }
+
+    fn bold() -> Style {
+        Style::default().add_modifier(Modifier::BOLD)
+    }
+
+    #[test]
+    fn nested_tags_stack() {
+        assert_eq!(
+            spans("<b>a<i>b</i>c</b>d"),
+            vec![
+                ("a".to_string(), bold()),
+                ("b".to_string(), bold().add_modifier(Modifier::ITALIC)),
+                ("c".to_string(), bold()),
+                ("d".to_string(), Style::default()),
+            ]
+        );
+        assert_eq!(
+            spans("<span fg='red' weight=\"bold\">x</span>"),
+            vec![("x".to_string(), bold().fg(Color::Red))]
+        );
+    }
+
+    #[test]
+    fn closing_tag_ends_only_its_own_kind() {
+        // a stray close is ignored rather than ending <b>
+        assert_eq!(
+            spans("<b>x</i> y</b>z"),
+            vec![
+                ("x".to_string(), bold()),
+                (" y".to_string(), bold()),
+                ("z".to_string(), Style::default()),
+            ]
+        );
+        // closing the outer tag also ends one left open inside it
+        assert_eq!(
+            spans("<b><i>x</b>y"),
+            vec![
+                ("x".to_string(), bold().add_modifier(Modifier::ITALIC)),
+                ("y".to_string(), Style::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_tags_are_text() {
+        assert_eq!(
+            spans("<em>x</em>"),
+            vec![("<em>x</em>".to_string(), Style::default())]
+        );
+    }
+
+    #[test]
+    fn entities_are_decoded() {
+        assert_eq!(
+            spans("&lt;b&gt; &amp; &quot;&apos; &nbsp;"),
+            vec![("<b> & \"' &nbsp;".to_string(), Style::default())]
+        );
+    }
+
+    #[test]
+    fn styled_placeholders_keep_their_name() {
+        let line = render("a {v:fg=#ff0000,bold} b", Style::default(), |text| {
+            text.replace("{v}", "42")
+        });
+        let styles: Vec<(&str, Style)> = line
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        assert_eq!(
+            styles,
+            vec![
+                ("a ", Style::default()),
+                ("42", bold().fg(Color::Rgb(255, 0, 0))),
+                (" b", Style::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn substituted_values_are_not_markup() {
+        let line = render("{v}", Style::default(), |_| "<b>x</b>".to_string());
+        assert_eq!(line.spans.len(), 1);
+        assert_eq!(line.spans[0].content, "<b>x</b>");
+    }
+
+    #[test]
+    fn combined_sgr_is_split() {
+        assert_eq!(split_sgr("\x1b[1;34mx"), "\x1b[1m\x1b[34mx");
+        assert_eq!(
+            split_sgr("\x1b[1;38;5;196;48;2;1;2;3mx"),
+            "\x1b[1m\x1b[38;5;196m\x1b[48;2;1;2;3mx"
+        );
+        assert_eq!(split_sgr("plain"), "plain");
+    }
+
+    #[test]
+    fn ansi_spans_lay_over_the_base() {
+        let base = Style::default().fg(Color::Green);
+        let spans = ansi_spans("\x1b[1;34mx\x1b[0my".to_string(), base);
+        let styles: Vec<(&str, Style)> = spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        // no empty span for the leading escapes, and a reset goes back to base
+        assert_eq!(
+            styles,
+            vec![
+                ("x", base.fg(Color::Blue).add_modifier(Modifier::BOLD)),
+                ("y", base),
+            ]
+        );
+    }
+}
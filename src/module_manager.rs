@@ -1,6 +1,8 @@
 use crate::config::Config;
 use crate::hyprland_ipc::HyprlandEvent;
 use crate::modules::*;
+use crate::tooltip;
+use ratatui::text::Text;
 use std::error::Error;
 use std::time::{Duration, Instant};
 
@@ -8,10 +10,55 @@ struct ModuleWithTimer {
     module: Box<dyn Module>,
     interval: Option<Duration>,
     last_update: Instant,
+    // last update error printed, so a failure repeating every tick is only
+    // printed once
+    last_error: Option<String>,
+}
+
+// Screen cells a module was drawn into on the last frame
+pub struct ModuleRegion {
+    pub index: usize,
+    pub x: u16,
+    pub width: u16,
 }
 
 pub struct ModuleManager {
     modules: Vec<ModuleWithTimer>,
+    regions: Vec<ModuleRegion>,
+    // module whose tooltip is drawn under the bar
+    tooltip: Option<usize>,
+}
+
+pub fn create_module(name: &str, config: &Config) -> Option<Box<dyn Module>> {
+    let module_config = config.module_configs.get(name).cloned().unwrap_or_default();
+
+    let module: Box<dyn Module> = match name {
+        "cpu" => Box::new(cpu::CpuModule::new(module_config)),
+        "memory" => Box::new(memory::MemoryModule::new(module_config)),
+        "disk" => Box::new(disk::DiskModule::new(module_config)),
+        "diskio" => Box::new(diskio::DiskIoModule::new(module_config)),
+        "battery" => Box::new(battery::BatteryModule::new(module_config)),
+        "clock" => Box::new(clock::ClockModule::new(module_config)),
+        "temperature" => Box::new(temperature::TemperatureModule::new(module_config)),
+        "workspaces" => Box::new(workspaces::WorkspaceModule::new(
+            module_config,
+            config.monitor.clone(),
+        )),
+        "window" => Box::new(window::WindowModule::new(
+            module_config,
+            config.monitor.clone(),
+        )),
+        "monitor" => Box::new(monitor::MonitorModule::new(
+            module_config,
+            config.monitor.clone(),
+        )),
+        "network" => Box::new(interweb::WebModule::new(module_config)),
+        _ if name == "custom" || name.starts_with("custom/") => {
+            Box::new(custom::CustomModule::new(name, module_config))
+        }
+        _ => return None,
+    };
+    Some(module)
 }
 
 impl ModuleManager {
@@ -29,26 +76,27 @@ impl ModuleManager {
                 continue;
             }
 
-            let module: Box<dyn Module> = match module_name.as_str() {
-                "cpu" => Box::new(cpu::CpuModule::new(module_config.clone())),
-                "memory" => Box::new(memory::MemoryModule::new(module_config.clone())),
-                "battery" => Box::new(battery::BatteryModule::new(module_config.clone())),
-                "clock" => Box::new(clock::ClockModule::new(module_config.clone())),
-                "workspaces" => Box::new(workspaces::WorkspaceModule::new(module_config.clone())),
-                "window" => Box::new(window::WindowModule::new(module_config.clone())),
-                "network" => Box::new(interweb::WebModule::new(module_config.clone())),
-                _ => continue,
+            let Some(module) = create_module(module_name, config) else {
+                continue;
             };
 
-            let interval = module_config.interval.map(Duration::from_millis);
+            let interval = module_config
+                .interval
+                .or_else(|| module.default_interval())
+                .map(Duration::from_millis);
 
             modules.push(ModuleWithTimer {
                 module,
                 interval,
                 last_update: Instant::now(),
+                last_error: None,
             });
         }
-        Self { modules }
+        Self {
+            modules,
+            regions: Vec::new(),
+            tooltip: None,
+        }
     }
 
     pub fn update_all(&mut self) -> Result<(), Box<dyn Error>> {
@@ -60,8 +108,18 @@ impl ModuleManager {
                 None => true,
             };
 
+            // one module failing shouldn't take the bar down with it
             if should_update {
-                module_timer.module.update()?;
+                match module_timer.module.update() {
+                    Ok(()) => module_timer.last_error = None,
+                    Err(e) => {
+                        let error = e.to_string();
+                        if module_timer.last_error.as_ref() != Some(&error) {
+                            eprintln!("{} update failed: {}", module_timer.module.name(), error);
+                            module_timer.last_error = Some(error);
+                        }
+                    }
+                }
                 module_timer.last_update = now;
             }
         }
@@ -81,16 +139,61 @@ impl ModuleManager {
                     }
                 }
             }
-            HyprlandEvent::ActiveWindowChanged(title) => {
+            HyprlandEvent::ActiveWindowChanged(window) => {
                 for module_timer in &mut self.modules {
-                    if module_timer.module.name() == "window" {
-                        if let Some(win_module) = module_timer
-                            .module
-                            .as_any_mut()
-                            .downcast_mut::<window::WindowModule>()
-                        {
-                            win_module.set_title(title.clone());
-                        }
+                    let module = module_timer.module.as_any_mut();
+                    if let Some(win_module) = module.downcast_mut::<window::WindowModule>() {
+                        win_module.set_window(window);
+                    } else if let Some(ws_module) =
+                        module.downcast_mut::<workspaces::WorkspaceModule>()
+                    {
+                        ws_module.windows_changed();
+                    }
+                }
+            }
+            HyprlandEvent::SpecialWorkspaceChanged(name, monitor) => {
+                for module_timer in &mut self.modules {
+                    if let Some(ws_module) = module_timer
+                        .module
+                        .as_any_mut()
+                        .downcast_mut::<workspaces::WorkspaceModule>()
+                    {
+                        ws_module.set_active_special(name.clone(), monitor.clone());
+                    }
+                }
+            }
+            HyprlandEvent::Urgent(address) => {
+                for module_timer in &mut self.modules {
+                    if let Some(ws_module) = module_timer
+                        .module
+                        .as_any_mut()
+                        .downcast_mut::<workspaces::WorkspaceModule>()
+                    {
+                        ws_module.set_urgent(address);
+                    }
+                }
+            }
+            HyprlandEvent::WindowsChanged => {
+                for module_timer in &mut self.modules {
+                    if let Some(ws_module) = module_timer
+                        .module
+                        .as_any_mut()
+                        .downcast_mut::<workspaces::WorkspaceModule>()
+                    {
+                        ws_module.windows_changed();
+                    }
+                }
+            }
+            HyprlandEvent::MonitorFocused(monitor, workspace) => {
+                for module_timer in &mut self.modules {
+                    let module = module_timer.module.as_any_mut();
+                    if let Some(ws_module) = module.downcast_mut::<workspaces::WorkspaceModule>() {
+                        ws_module.set_focused_monitor(monitor.clone(), workspace);
+                    } else if let Some(win_module) = module.downcast_mut::<window::WindowModule>() {
+                        win_module.set_focused_monitor(monitor.clone());
+                    } else if let Some(mon_module) = module.downcast_mut::<monitor::MonitorModule>()
+                    {
+                        mon_module.set_focused_monitor(monitor.clone());
                     }
                 }
             }
@@ -98,18 +201,83 @@ impl ModuleManager {
         }
     }
 
-    pub fn get_modules_for_position(&self, pos: ModulePosition) -> Vec<&Box<dyn Module>> {
+    pub fn get_modules_for_position(&self, pos: ModulePosition) -> Vec<(usize, &dyn Module)> {
         self.modules
             .iter()
-            .map(|mt| &mt.module)
-            .filter(|m| m.position() == pos)
+            .map(|mt| mt.module.as_ref())
+            .enumerate()
+            .filter(|(_, m)| m.position() == pos)
             .collect()
     }
 
+    pub fn get_module(&self, name: &str) -> Option<(usize, &dyn Module)> {
+        self.modules
+            .iter()
+            .map(|mt| mt.module.as_ref())
+            .enumerate()
+            .find(|(_, m)| m.name() == name)
+    }
+
+    pub fn set_regions(&mut self, regions: Vec<ModuleRegion>) {
+        self.regions = regions;
+    }
+
+    // (module index, x relative to the module) under the given column;
+    // later sections are drawn on top, so they win
+    fn module_at(&self, x: u16) -> Option<(usize, u16)> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|r| x >= r.x && x < r.x + r.width)
+            .map(|r| (r.index, x - r.x))
+    }
+
     pub fn handle_click(&mut self, x: u16, y: u16) -> Result<(), Box<dyn Error>> {
-        for module_timer in &mut self.modules {
-            module_timer.module.on_click(x, y)?;
+        if let Some((index, x)) = self.module_at(x) {
+            self.modules[index].module.on_click(x, y)?;
         }
         Ok(())
     }
+
+    pub fn handle_scroll(&mut self, x: u16, delta: i32) -> Result<(), Box<dyn Error>> {
+        if let Some((index, _)) = self.module_at(x) {
+            self.modules[index].module.on_scroll(delta)?;
+        }
+        Ok(())
+    }
+
+    pub fn handle_hover(&mut self, x: u16) {
+        self.tooltip = self
+            .module_at(x)
+            .map(|(index, _)| index)
+            .filter(|&index| self.modules[index].module.tooltip().is_some());
+    }
+
+    // inline pins the tooltip under the bar, otherwise it opens in a window
+    pub fn handle_tooltip_click(&mut self, x: u16, inline: bool) -> Result<(), Box<dyn Error>> {
+        let Some((index, _)) = self.module_at(x) else {
+            return Ok(());
+        };
+        let module = &self.modules[index].module;
+        if module.tooltip().is_none() {
+            return Ok(());
+        }
+
+        if inline {
+            self.tooltip = match self.tooltip {
+                Some(current) if current == index => None,
+                _ => Some(index),
+            };
+            Ok(())
+        } else {
+            tooltip::open_window(module.name())
+        }
+    }
+
+    // the tooltip to draw and the column of the module it belongs to
+    pub fn tooltip(&self) -> Option<(u16, Text<'_>)> {
+        let index = self.tooltip?;
+        let region = self.regions.iter().find(|r| r.index == index)?;
+        Some((region.x, self.modules[index].module.tooltip()?))
+    }
 }
@@ -1,33 +1,274 @@
-use super::{Module, ModuleConfig, ModulePosition};
+use super::{Module, ModuleConfig, ModulePosition, tooltip_line};
+use crate::markup;
+use crate::notify::{Notifier, NotifySend, Urgency};
 use crate::styles;
-use battery::{Battery, Manager, State};
-use ratatui::{
-    style::Style,
-    text::{self, Span},
-};
+use battery::units::{energy::watt_hour, power::watt};
+use battery::{Manager, State};
+use ratatui::text::{Line, Text};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::process::Command;
+
+thread_local! {
+    // Manager isn't Send, so it can't live on the module; modules are only
+    // ever updated from the main thread, which keeps this one around
+    static MANAGER: Option<Manager> = Manager::new().ok();
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BatteryOptions {
+    // percentages that trigger a notification while discharging
+    pub warning: u8,
+    pub critical: u8,
+    // run through `sh -c` on reaching critical, e.g. "systemctl suspend"
+    pub critical_command: Option<String>,
+    // what to show on machines without a battery
+    pub missing: MissingBattery,
+    pub format_ac: String,
+    pub ac_icon: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum MissingBattery {
+    Hide,
+    // running off the mains, shown with `format_ac`
+    Ac,
+}
+
+impl Default for BatteryOptions {
+    fn default() -> Self {
+        Self {
+            warning: 20,
+            critical: 10,
+            critical_command: None,
+            missing: MissingBattery::Hide,
+            format_ac: "{icon} AC".to_string(),
+            ac_icon: "󰚥".to_string(),
+        }
+    }
+}
+
+// The lowest level already notified about since the last charge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Alert {
+    None,
+    Warning,
+    Critical,
+}
+
+// One battery pack at a point in time
+#[derive(Debug, Clone)]
+pub struct BatteryReading {
+    // Wh
+    pub energy: f64,
+    pub energy_full: f64,
+    pub energy_full_design: f64,
+    // W, always positive
+    pub power: f64,
+    pub state: State,
+    pub cycles: Option<u32>,
+}
+
+// Every pack combined, as if the machine had a single battery
+#[derive(Debug, Clone)]
+pub struct BatteryStatus {
+    pub level: u8,
+    pub state: State,
+    // W
+    pub power: f64,
+    // until full when charging, until empty otherwise
+    pub hours_left: Option<f64>,
+    pub health: u8,
+    // the most worn pack's count
+    pub cycles: Option<u32>,
+    pub packs: usize,
+}
+
+impl BatteryStatus {
+    pub fn from_readings(readings: &[BatteryReading]) -> Self {
+        let energy: f64 = readings.iter().map(|r| r.energy).sum();
+        let full: f64 = readings.iter().map(|r| r.energy_full).sum();
+        let design: f64 = readings.iter().map(|r| r.energy_full_design).sum();
+        let power: f64 = readings.iter().map(|r| r.power).sum();
+
+        // one pack charging while the other sits full is still charging
+        let any = |state: State| readings.iter().any(|r| r.state == state);
+        let state = if any(State::Charging) {
+            State::Charging
+        } else if any(State::Discharging) {
+            State::Discharging
+        } else if !readings.is_empty() && readings.iter().all(|r| r.state == State::Full) {
+            State::Full
+        } else if !readings.is_empty() && readings.iter().all(|r| r.state == State::Empty) {
+            State::Empty
+        } else {
+            State::Unknown
+        };
+
+        let hours_left = match state {
+            State::Charging if power > 0.0 => Some((full - energy).max(0.0) / power),
+            State::Discharging if power > 0.0 => Some(energy / power),
+            _ => None,
+        };
+
+        Self {
+            level: percentage(energy, full),
+            state,
+            power,
+            hours_left,
+            health: percentage(full, design),
+            cycles: readings.iter().filter_map(|r| r.cycles).max(),
+            packs: readings.len(),
+        }
+    }
+
+    fn charging(&self) -> bool {
+        matches!(self.state, State::Charging | State::Full)
+    }
+}
+
+fn percentage(part: f64, whole: f64) -> u8 {
+    if whole > 0.0 {
+        (part / whole * 100.0).round().clamp(0.0, 100.0) as u8
+    } else {
+        0
+    }
+}
+
+fn state_label(state: State) -> &'static str {
+    match state {
+        State::Charging => "Charging",
+        State::Discharging => "Discharging",
+        State::Full => "Charged",
+        State::Empty => "Empty",
+        _ => "Unknown",
+    }
+}
+
+// "2:05"
+fn format_hours(hours: f64) -> String {
+    let minutes = (hours * 60.0).round() as u64;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+fn read_batteries() -> Result<Vec<BatteryReading>, Box<dyn Error>> {
+    MANAGER.with(|manager| {
+        // no power_supply class to look at means no battery either
+        let Some(manager) = manager else {
+            return Ok(Vec::new());
+        };
+        let mut readings = Vec::new();
+
+        for battery in manager.batteries()? {
+            let battery = battery?;
+            readings.push(BatteryReading {
+                energy: battery.energy().get::<watt_hour>() as f64,
+                energy_full: battery.energy_full().get::<watt_hour>() as f64,
+                energy_full_design: battery.energy_full_design().get::<watt_hour>() as f64,
+                power: battery.energy_rate().get::<watt>().abs() as f64,
+                state: battery.state(),
+                cycles: battery.cycle_count(),
+            });
+        }
+        Ok(readings)
+    })
+}
+
+// None on machines without a battery
+fn read_status() -> Result<Option<BatteryStatus>, Box<dyn Error>> {
+    let readings = read_batteries()?;
+    Ok((!readings.is_empty()).then(|| BatteryStatus::from_readings(&readings)))
+}
 
 pub struct BatteryModule {
-    level: u8,
-    charging: bool,
+    status: Option<BatteryStatus>,
+    alerted: Alert,
+    // off for copies of the module other than the bar's
+    alerts: bool,
+    notifier: Box<dyn Notifier>,
     config: ModuleConfig,
+    options: BatteryOptions,
 }
 
 impl BatteryModule {
     pub fn new(config: ModuleConfig) -> Self {
+        Self::with_notifier(config, Box::new(NotifySend::default()))
+    }
+
+    // alerts go through notifier instead of notify-send
+    pub fn with_notifier(config: ModuleConfig, notifier: Box<dyn Notifier>) -> Self {
         Self {
-            level: 0,
-            charging: false,
+            // read up front so a desktop doesn't flash an empty battery
+            // until the first update
+            status: read_status().unwrap_or(None),
+            alerted: Alert::None,
+            alerts: true,
+            notifier,
+            options: config.options(),
             config,
         }
     }
 
-    fn get_icon(&self) -> &'static str {
-        if self.charging {
+    // no notifications and no critical_command, e.g. in the tooltip window,
+    // where the bar's own module has already sent them
+    pub fn disable_alerts(&mut self) {
+        self.alerts = false;
+    }
+
+    fn notify(&self, summary: &str, body: &str, urgency: Urgency) {
+        if let Err(e) = self.notifier.notify(summary, body, urgency) {
+            eprintln!("Battery notification failed: {}", e);
+        }
+    }
+
+    // Notifies once per threshold crossed; only charging re-arms them, so a
+    // level bouncing around a threshold doesn't notify over and over
+    fn check_alerts(&mut self) {
+        if !self.alerts {
+            return;
+        }
+        let Some(status) = &self.status else {
+            return;
+        };
+        if status.charging() {
+            self.alerted = Alert::None;
+            return;
+        }
+        if status.state != State::Discharging {
+            return;
+        }
+
+        let level = status.level;
+        if level <= self.options.critical && self.alerted < Alert::Critical {
+            self.alerted = Alert::Critical;
+            self.notify(
+                "Battery critical",
+                &format!("{}% remaining, plug in now", level),
+                Urgency::Critical,
+            );
+
+            if let Some(command) = &self.options.critical_command
+                && let Err(e) = Command::new("sh").arg("-c").arg(command).spawn()
+            {
+                eprintln!("Battery critical command failed: {}", e);
+            }
+        } else if level <= self.options.warning && self.alerted < Alert::Warning {
+            self.alerted = Alert::Warning;
+            self.notify(
+                "Battery low",
+                &format!("{}% remaining", level),
+                Urgency::Normal,
+            );
+        }
+    }
+
+    fn get_icon(status: &BatteryStatus) -> &'static str {
+        if status.charging() {
             return "󰂄";
         }
 
-        match self.level {
+        match status.level {
             90..=100 => "󰁹",
             80..=89 => "󰂂",
             70..=79 => "󰂁",
@@ -52,76 +293,286 @@ impl Module for BatteryModule {
     }
 
     fn update(&mut self) -> Result<(), Box<dyn Error>> {
-        let manager = Manager::new()?;
-        let mut batteries = manager.batteries()?;
-
-        if let Some(Ok(battery)) = batteries.next() {
-            self.level = (battery.state_of_charge().value * 100.0) as u8;
-            self.charging = matches!(battery.state(), State::Charging | State::Full);
-        }
+        self.status = read_status()?;
+        self.check_alerts();
         Ok(())
     }
 
-    fn render(&self) -> Span {
+    fn render(&self) -> Line<'_> {
+        let Some(status) = &self.status else {
+            return match self.options.missing {
+                MissingBattery::Hide => Line::default(),
+                MissingBattery::Ac => markup::render(
+                    &self.options.format_ac,
+                    styles::battery_style(true, 100),
+                    |text| text.replace("{icon}", &self.options.ac_icon),
+                ),
+            };
+        };
         let format = self.config.format.as_deref().unwrap_or("{icon} {level}%");
-        let icon = self.get_icon();
+        let icon = Self::get_icon(status);
+
+        markup::render(
+            format,
+            styles::battery_style(status.charging(), status.level),
+            |text| {
+                text.replace("{icon}", icon)
+                    .replace("{level}", &status.level.to_string())
+                    .replace(
+                        "{time}",
+                        &status.hours_left.map(format_hours).unwrap_or_default(),
+                    )
+                    .replace("{power}", &format!("{:.1}", status.power))
+                    .replace("{health}", &status.health.to_string())
+                    .replace(
+                        "{cycles}",
+                        &status.cycles.map(|c| c.to_string()).unwrap_or_default(),
+                    )
+                    .replace("{state}", state_label(status.state))
+            },
+        )
+    }
 
-        let text = format
-            .replace("{icon}", icon)
-            .replace("{level}", &self.level.to_string());
+    fn tooltip(&self) -> Option<Text<'_>> {
+        let status = self.status.as_ref()?;
+        let time_label = match status.state {
+            State::Charging => "To full",
+            _ => "To empty",
+        };
 
-        Span::styled(text, styles::battery_style(self.charging, self.level))
+        let mut lines = vec![
+            tooltip_line("Level", format!("{}%", status.level)),
+            tooltip_line("State", state_label(status.state)),
+            tooltip_line(
+                time_label,
+                status
+                    .hours_left
+                    .map(format_hours)
+                    .unwrap_or_else(|| "N/A".to_string()),
+            ),
+            tooltip_line("Power", format!("{:.1} W", status.power)),
+            tooltip_line("Health", format!("{}%", status.health)),
+        ];
+        if let Some(cycles) = status.cycles {
+            lines.push(tooltip_line("Cycles", cycles.to_string()));
+        }
+        if status.packs > 1 {
+            lines.push(tooltip_line("Packs", status.packs.to_string()));
+        }
+        Some(Text::from(lines))
     }
 
-    fn on_click(&mut self, x: u16, y: u16) -> Result<(), Box<dyn Error>> {
-        let manager = Manager::new()?;
-        let mut batteries = manager.batteries()?;
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
 
-        if let Some(Ok(battery)) = batteries.next() {
-            let state = match battery.state() {
-                State::Charging => "Charging",
-                State::Discharging => "Discharging",
-                State::Full => "Charged",
-                State::Empty => "Empty",
-                _ => "Unknown",
-            };
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn reading(energy: f64, power: f64, state: State) -> BatteryReading {
+        BatteryReading {
+            energy,
+            energy_full: 50.0,
+            energy_full_design: 50.0,
+            power,
+            state,
+            cycles: None,
+        }
+    }
+
+    #[test]
+    fn one_pack_charging_is_charging() {
+        let status = BatteryStatus::from_readings(&[
+            reading(25.0, 10.0, State::Charging),
+            reading(50.0, 0.0, State::Full),
+        ]);
+
+        assert_eq!(status.state, State::Charging);
+        assert_eq!(status.level, 75);
+        assert_eq!(status.packs, 2);
+    }
 
-            let time_to_full = battery
-                .time_to_full()
-                .map(|t| format!("{:.0} minutes", t.get::<battery::units::time::minute>()))
-                .unwrap_or_else(|| "N/A".to_string());
+    #[test]
+    fn hours_left_while_charging() {
+        let status = BatteryStatus::from_readings(&[reading(30.0, 10.0, State::Charging)]);
+        assert_eq!(status.hours_left, Some(2.0));
+    }
 
-            let time_to_empty = battery
-                .time_to_empty()
-                .map(|t| format!("{:.0} minutes", t.get::<battery::units::time::minute>()))
-                .unwrap_or_else(|| "N/A".to_string());
+    #[test]
+    fn hours_left_while_discharging() {
+        let status = BatteryStatus::from_readings(&[
+            reading(30.0, 8.0, State::Discharging),
+            reading(10.0, 2.0, State::Discharging),
+        ]);
+        assert_eq!(status.power, 10.0);
+        assert_eq!(status.hours_left, Some(4.0));
+    }
 
-            let health = (battery.state_of_health().value * 100.0) as u8;
+    #[test]
+    fn no_hours_left_without_power() {
+        let charging = BatteryStatus::from_readings(&[reading(30.0, 0.0, State::Charging)]);
+        assert_eq!(charging.hours_left, None);
 
-            let info = format!(
-                "Battery: {}%\nState: {}\nHealth: {}%\nTime to full: {}\nTime to empty: {}",
-                self.level, state, health, time_to_full, time_to_empty
-            );
+        let discharging = BatteryStatus::from_readings(&[reading(30.0, 0.0, State::Discharging)]);
+        assert_eq!(discharging.hours_left, None);
+    }
+
+    #[test]
+    fn health_from_full_and_design() {
+        let worn = BatteryReading {
+            energy_full: 40.0,
+            ..reading(20.0, 5.0, State::Discharging)
+        };
+        let status = BatteryStatus::from_readings(&[worn, reading(50.0, 0.0, State::Full)]);
+
+        // 90 of 100 Wh designed
+        assert_eq!(status.health, 90);
+        assert_eq!(status.level, 78);
+    }
+
+    #[test]
+    fn cycles_of_the_most_worn_pack() {
+        let status = BatteryStatus::from_readings(&[
+            BatteryReading {
+                cycles: Some(120),
+                ..reading(20.0, 5.0, State::Discharging)
+            },
+            BatteryReading {
+                cycles: Some(480),
+                ..reading(20.0, 5.0, State::Discharging)
+            },
+            reading(20.0, 5.0, State::Discharging),
+        ]);
+        assert_eq!(status.cycles, Some(480));
+
+        let unknown = BatteryStatus::from_readings(&[reading(20.0, 5.0, State::Discharging)]);
+        assert_eq!(unknown.cycles, None);
+    }
 
-            std::process::Command::new("notify-send")
-                .arg("Battery Info")
-                .arg(&info)
-                .arg("-t")
-                .arg("5000")
-                .spawn()?;
+    // keeps (summary, urgency) of everything sent
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<(String, Urgency)>>>);
+
+    impl Notifier for Recorder {
+        fn notify(
+            &self,
+            summary: &str,
+            _body: &str,
+            urgency: Urgency,
+        ) -> Result<(), Box<dyn Error>> {
+            self.0.lock().unwrap().push((summary.to_string(), urgency));
+            Ok(())
         }
-        Ok(())
     }
 
-    fn on_scroll(&mut self, delta: i32) -> Result<(), Box<dyn Error>> {
-        Ok(())
+    impl Recorder {
+        fn take(&self) -> Vec<(String, Urgency)> {
+            std::mem::take(&mut self.0.lock().unwrap())
+        }
     }
 
-    fn as_any(&self) -> &dyn std::any::Any {
-        self
+    fn alerting_module() -> (BatteryModule, Recorder) {
+        let recorder = Recorder::default();
+        let module =
+            BatteryModule::with_notifier(ModuleConfig::default(), Box::new(recorder.clone()));
+        (module, recorder)
     }
 
-    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
-        self
+    // sets the level (out of 50 Wh) and runs the alert check
+    fn step(module: &mut BatteryModule, level: f64, state: State) {
+        let energy = level / 2.0;
+        module.status = Some(BatteryStatus::from_readings(&[reading(energy, 5.0, state)]));
+        module.check_alerts();
+    }
+
+    #[test]
+    fn warns_once_per_crossing() {
+        let (mut module, recorder) = alerting_module();
+
+        step(&mut module, 30.0, State::Discharging);
+        assert!(recorder.take().is_empty());
+
+        step(&mut module, 20.0, State::Discharging);
+        step(&mut module, 18.0, State::Discharging);
+        step(&mut module, 15.0, State::Discharging);
+        assert_eq!(
+            recorder.take(),
+            [("Battery low".to_string(), Urgency::Normal)]
+        );
+    }
+
+    #[test]
+    fn critical_after_warning() {
+        let (mut module, recorder) = alerting_module();
+
+        step(&mut module, 19.0, State::Discharging);
+        step(&mut module, 10.0, State::Discharging);
+        step(&mut module, 5.0, State::Discharging);
+        assert_eq!(
+            recorder.take(),
+            [
+                ("Battery low".to_string(), Urgency::Normal),
+                ("Battery critical".to_string(), Urgency::Critical),
+            ]
+        );
+    }
+
+    #[test]
+    fn bouncing_level_does_not_repeat() {
+        let (mut module, recorder) = alerting_module();
+
+        for level in [20.0, 22.0, 20.0, 24.0, 19.0, 10.0, 12.0, 10.0, 14.0, 9.0] {
+            step(&mut module, level, State::Discharging);
+        }
+        assert_eq!(recorder.take().len(), 2);
+
+        // an idle reading doesn't re-arm them, only charging does
+        step(&mut module, 30.0, State::Unknown);
+        step(&mut module, 9.0, State::Discharging);
+        assert!(recorder.take().is_empty());
+    }
+
+    #[test]
+    fn charging_rearms() {
+        let (mut module, recorder) = alerting_module();
+
+        step(&mut module, 8.0, State::Discharging);
+        assert_eq!(recorder.take().len(), 1);
+
+        step(&mut module, 9.0, State::Charging);
+        step(&mut module, 8.0, State::Discharging);
+        assert_eq!(
+            recorder.take(),
+            [("Battery critical".to_string(), Urgency::Critical)]
+        );
+    }
+
+    #[test]
+    fn disabled_alerts_send_nothing() {
+        let (mut module, recorder) = alerting_module();
+        module.disable_alerts();
+
+        step(&mut module, 19.0, State::Discharging);
+        step(&mut module, 5.0, State::Discharging);
+        assert!(recorder.take().is_empty());
+    }
+
+    #[test]
+    fn no_readings() {
+        let status = BatteryStatus::from_readings(&[]);
+
+        assert_eq!(status.state, State::Unknown);
+        assert_eq!(status.level, 0);
+        assert_eq!(status.health, 0);
+        assert_eq!(status.hours_left, None);
+        assert_eq!(status.cycles, None);
+        assert_eq!(status.packs, 0);
     }
 }
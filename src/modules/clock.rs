@@ -1,21 +1,111 @@
 use super::{Module, ModuleConfig, ModulePosition};
+use crate::markup;
 use crate::styles;
-use chrono::Local;
-use ratatui::{style::Style, text::Span};
+use chrono::{Local, Utc};
+use chrono_tz::Tz;
+use ratatui::text::Line;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ClockOptions {
+    // IANA name such as "Europe/Berlin"; local time if unset
+    pub timezone: Option<String>,
+    // cycled through after `format` by scrolling, or clicking when the
+    // calendar is off
+    pub formats: Vec<String>,
+    // open a month calendar on click
+    pub calendar: bool,
+}
+
+impl Default for ClockOptions {
+    fn default() -> Self {
+        Self {
+            timezone: None,
+            formats: vec!["%a %d %b %Y".to_string(), "W%V %G".to_string()],
+            calendar: true,
+        }
+    }
+}
+
 pub struct ClockModule {
-    time: String,
+    time: Line<'static>,
+    timezone: Option<Tz>,
+    // index into formats()
+    current: usize,
     config: ModuleConfig,
+    options: ClockOptions,
 }
 
 impl ClockModule {
     pub fn new(config: ModuleConfig) -> Self {
+        let options: ClockOptions = config.options();
+        let timezone = options
+            .timezone
+            .as_deref()
+            .and_then(|name| match name.parse::<Tz>() {
+                Ok(tz) => Some(tz),
+                Err(e) => {
+                    eprintln!("Unknown timezone {}: {}", name, e);
+                    None
+                }
+            });
+
         Self {
-            time: String::new(),
+            time: Line::default(),
+            timezone,
+            current: 0,
+            options,
             config,
         }
     }
+
+    fn formats(&self) -> Vec<&str> {
+        let main = self.config.format.as_deref().unwrap_or("%H:%M:%S");
+        std::iter::once(main)
+            .chain(self.options.formats.iter().map(String::as_str))
+            .collect()
+    }
+
+    fn refresh(&mut self) {
+        let formats = self.formats();
+        let format = formats[self.current % formats.len()];
+
+        let zoned = self.timezone.map(|tz| Utc::now().with_timezone(&tz));
+        let local = Local::now();
+
+        // strftime runs on each markup run, not on the markup itself
+        self.time = markup::render(format, styles::clock_style(), |text| match zoned {
+            Some(now) => {
+                let text = text.replace("{tz}", now.timezone().name());
+                now.format(&text).to_string()
+            }
+            // chrono only knows the local offset, not the zone's name
+            None => local.format(&text.replace("{tz}", "%Z")).to_string(),
+        });
+    }
+
+    // the bar itself, run with --calendar in a kitty window of its own;
+    // float it with a windowrule on the jlessbar-calendar class
+    fn open_calendar(&self) -> Result<(), Box<dyn Error>> {
+        let mut command = std::process::Command::new("kitty");
+        command
+            .args(["--class", "jlessbar-calendar", "-e"])
+            .arg(std::env::current_exe()?)
+            .arg("--calendar");
+        if let Some(tz) = self.timezone {
+            command.args(["--timezone", tz.name()]);
+        }
+        command.spawn()?;
+        Ok(())
+    }
+
+    fn cycle(&mut self, step: i32) {
+        let count = self.formats().len() as i32;
+        self.current = (self.current as i32 + step).rem_euclid(count) as usize;
+        self.refresh();
+    }
 }
 
 impl Module for ClockModule {
@@ -28,15 +118,25 @@ impl Module for ClockModule {
     }
 
     fn update(&mut self) -> Result<(), Box<dyn Error>> {
-        let format = self.config.format.as_deref().unwrap_or("%H:%M:%S");
+        self.refresh();
+        Ok(())
+    }
 
-        self.time = Local::now().format(format).to_string();
+    fn render(&self) -> Line<'_> {
+        self.time.clone()
+    }
 
+    fn on_click(&mut self, _x: u16, _y: u16) -> Result<(), Box<dyn Error>> {
+        if self.options.calendar {
+            return self.open_calendar();
+        }
+        self.cycle(1);
         Ok(())
     }
 
-    fn render(&self) -> Span {
-        Span::styled(&self.time, styles::clock_style())
+    fn on_scroll(&mut self, delta: i32) -> Result<(), Box<dyn Error>> {
+        self.cycle(delta.signum());
+        Ok(())
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -1,13 +1,37 @@
-use super::{Module, ModuleConfig, ModulePosition};
+use super::{Module, ModuleConfig, ModulePosition, tooltip_line};
+use crate::markup;
 use crate::styles;
-use ratatui::{style::Style, text::Span};
+use ratatui::text::{Line, Text};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::error::Error;
 use sysinfo::System;
 
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CpuOptions {
+    // samples kept for {history}
+    pub history_length: usize,
+}
+
+impl Default for CpuOptions {
+    fn default() -> Self {
+        Self { history_length: 10 }
+    }
+}
+
 pub struct CpuModule {
     usage: f64,
+    cores: Vec<f64>,
+    // average across cores, MHz
+    frequency: u64,
+    load: (f64, f64, f64),
+    history: VecDeque<f64>,
     system: System,
     config: ModuleConfig,
+    options: CpuOptions,
     icon: &'static str,
 }
 
@@ -15,11 +39,31 @@ impl CpuModule {
     pub fn new(config: ModuleConfig) -> Self {
         Self {
             usage: 0.0,
+            cores: Vec::new(),
+            frequency: 0,
+            load: (0.0, 0.0, 0.0),
+            history: VecDeque::new(),
             system: System::new_all(),
+            options: config.options(),
             config,
             icon: "󰻠 ",
         }
     }
+
+    fn max_core(&self) -> f64 {
+        self.cores.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+// One block character per percentage sample
+fn sparkline<'a>(values: impl IntoIterator<Item = &'a f64>) -> String {
+    values
+        .into_iter()
+        .map(|v| {
+            let level = (v.clamp(0.0, 100.0) / 100.0 * (BLOCKS.len() - 1) as f64).round();
+            BLOCKS[level as usize]
+        })
+        .collect()
 }
 
 impl Module for CpuModule {
@@ -42,18 +86,66 @@ impl Module for CpuModule {
                 total += cpu.cpu_usage() as f64;
             }
             self.usage = (total / cpus.len() as f64 * 10.0).round() / 10.0;
+            self.cores = cpus.iter().map(|cpu| cpu.cpu_usage() as f64).collect();
+            self.frequency =
+                cpus.iter().map(|cpu| cpu.frequency()).sum::<u64>() / cpus.len() as u64;
+        }
+
+        let load = System::load_average();
+        self.load = (load.one, load.five, load.fifteen);
+
+        self.history.push_back(self.usage);
+        while self.history.len() > self.options.history_length {
+            self.history.pop_front();
         }
         Ok(())
     }
 
-    fn render(&self) -> Span {
-        let format_str = self.config.format.as_deref().unwrap_or("{icon} {usage}%");
+    fn render(&self) -> Line<'_> {
+        let format = self.config.format.as_deref().unwrap_or("{icon} {usage}%");
+
+        markup::render(format, styles::cpu_style(), |text| {
+            let mut text = text
+                .replace("{icon}", self.icon)
+                .replace("{usage}", &format!("{:.0}", self.usage))
+                .replace("{max}", &format!("{:.0}", self.max_core()))
+                .replace("{cores}", &sparkline(&self.cores))
+                .replace("{history}", &sparkline(&self.history))
+                .replace(
+                    "{freq}",
+                    &format!("{:.1}GHz", self.frequency as f64 / 1000.0),
+                )
+                .replace("{load}", &format!("{:.2}", self.load.0))
+                .replace("{load5}", &format!("{:.2}", self.load.1))
+                .replace("{load15}", &format!("{:.2}", self.load.2));
 
-        let text = format_str
-            .replace("{icon}", self.icon)
-            .replace("{usage}", &format!("{:.0}", self.usage));
+            for (i, core) in self.cores.iter().enumerate() {
+                text = text.replace(&format!("{{core{}}}", i), &format!("{:.0}", core));
+            }
+            text
+        })
+    }
 
-        Span::styled(text, styles::cpu_style())
+    fn tooltip(&self) -> Option<Text<'_>> {
+        let mut lines = vec![
+            tooltip_line("Usage", format!("{:.0}%", self.usage)),
+            tooltip_line(
+                "Frequency",
+                format!("{:.1} GHz", self.frequency as f64 / 1000.0),
+            ),
+            tooltip_line(
+                "Load",
+                format!("{:.2} {:.2} {:.2}", self.load.0, self.load.1, self.load.2),
+            ),
+            tooltip_line("History", sparkline(&self.history)),
+        ];
+        lines.extend(self.cores.iter().enumerate().map(|(i, core)| {
+            tooltip_line(
+                &format!("Core {}", i),
+                format!("{:>3.0}% {}", core, sparkline([core])),
+            )
+        }));
+        Some(Text::from(lines))
     }
 
     fn on_click(&mut self, _x: u16, _y: u16) -> Result<(), Box<dyn Error>> {
@@ -0,0 +1,236 @@
+use super::{Module, ModuleConfig, ModulePosition};
+use crate::markup;
+use crate::styles;
+use ratatui::text::{Line, Text};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Handle;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CustomOptions {
+    // run through `sh -c` on every update; the first line of its output is
+    // shown as {output}, any further lines become the tooltip
+    pub command: String,
+    // ms before a run that hasn't finished is killed
+    pub timeout: u64,
+    // also run through `sh -c`
+    pub on_click: Option<String>,
+    // read the output as markup rather than plain text; ANSI escapes are
+    // kept either way
+    pub markup: bool,
+}
+
+impl Default for CustomOptions {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            timeout: 10000,
+            on_click: None,
+            markup: false,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Output {
+    text: String,
+    details: Vec<String>,
+}
+
+// `custom` or `custom/<name>`, so a config can have as many as it likes
+pub struct CustomModule {
+    name: String,
+    // filled in by the latest run that finished
+    output: Arc<Mutex<Output>>,
+    running: Arc<AtomicBool>,
+    config: ModuleConfig,
+    options: CustomOptions,
+}
+
+impl CustomModule {
+    pub fn new(name: &str, config: ModuleConfig) -> Self {
+        let module = Self {
+            name: name.to_string(),
+            output: Arc::new(Mutex::new(Output::default())),
+            running: Arc::new(AtomicBool::new(false)),
+            options: config.options(),
+            config,
+        };
+        // first output without waiting a whole interval
+        module.start();
+        module
+    }
+
+    // Runs the command on the runtime, so a slow script never holds up the
+    // bar; a run still going when the next update comes is left alone
+    fn start(&self) {
+        if self.options.command.is_empty() || self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let Ok(runtime) = Handle::try_current() else {
+            self.running.store(false, Ordering::SeqCst);
+            return;
+        };
+
+        let mut command = tokio::process::Command::new("sh");
+        command
+            .arg("-c")
+            .arg(&self.options.command)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        let timeout = Duration::from_millis(self.options.timeout);
+        let name = self.name.clone();
+        let output = Arc::clone(&self.output);
+        let running = Arc::clone(&self.running);
+
+        runtime.spawn(async move {
+            match tokio::time::timeout(timeout, command.output()).await {
+                Ok(Ok(result)) => {
+                    let stdout = String::from_utf8_lossy(&result.stdout);
+                    let mut lines = stdout.lines().map(str::to_string);
+
+                    if let Ok(mut output) = output.lock() {
+                        output.text = lines.next().unwrap_or_default();
+                        output.details = lines.collect();
+                    }
+                }
+                Ok(Err(e)) => eprintln!("{} command failed: {}", name, e),
+                Err(_) => eprintln!("{} command timed out", name),
+            }
+            running.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+impl Module for CustomModule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn position(&self) -> ModulePosition {
+        self.config.position.clone()
+    }
+
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        self.start();
+        Ok(())
+    }
+
+    fn default_interval(&self) -> Option<u64> {
+        Some(5000)
+    }
+
+    fn render(&self) -> Line<'_> {
+        let Ok(output) = self.output.lock() else {
+            return Line::default();
+        };
+        // nothing printed hides the module, format and all
+        if output.text.is_empty() {
+            return Line::default();
+        }
+        let format = self.config.format.as_deref().unwrap_or("{output}");
+
+        if self.options.markup {
+            let format = format.replace("{output}", &output.text);
+            return markup::render(&format, styles::custom_style(), |text| text.to_string());
+        }
+        markup::render(format, styles::custom_style(), |text| {
+            text.replace("{output}", &output.text)
+        })
+    }
+
+    fn on_click(&mut self, _x: u16, _y: u16) -> Result<(), Box<dyn Error>> {
+        if let Some(command) = &self.options.on_click {
+            Command::new("sh").arg("-c").arg(command).spawn()?;
+        }
+        Ok(())
+    }
+
+    fn tooltip(&self) -> Option<Text<'_>> {
+        let output = self.output.lock().ok()?;
+        if output.details.is_empty() {
+            return None;
+        }
+
+        let style = styles::tooltip_value_style();
+        let lines: Vec<Line> = output
+            .details
+            .iter()
+            .map(|line| {
+                if self.options.markup {
+                    markup::render(line, style, |text| text.to_string())
+                } else {
+                    markup::plain(line, style)
+                }
+            })
+            .collect();
+        Some(Text::from(lines))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::{config_with, render_text};
+
+    fn custom(options: serde_json::Value) -> CustomModule {
+        CustomModule::new("custom/test", config_with(options))
+    }
+
+    async fn finished(module: &CustomModule) {
+        while module.running.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn first_line_shown_rest_in_tooltip() {
+        let module = custom(serde_json::json!({ "command": "printf 'up\\nmore\\ndetail\\n'" }));
+        finished(&module).await;
+
+        assert_eq!(render_text(&module), "up");
+        assert_eq!(module.tooltip().map(|text| text.lines.len()), Some(2));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn slow_command_times_out() {
+        let module = custom(serde_json::json!({ "command": "sleep 5; echo late", "timeout": 100 }));
+        tokio::time::timeout(Duration::from_secs(2), finished(&module))
+            .await
+            .unwrap();
+
+        assert_eq!(render_text(&module), "");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn markup_only_when_asked() {
+        let command = "printf '<b>x</b>\\n<i>y</i>\\n'";
+        let plain = custom(serde_json::json!({ "command": command }));
+        let styled = custom(serde_json::json!({ "command": command, "markup": true }));
+        finished(&plain).await;
+        finished(&styled).await;
+
+        assert_eq!(render_text(&plain), "<b>x</b>");
+        assert_eq!(render_text(&styled), "x");
+        let tooltip = |module: &CustomModule| {
+            module.tooltip().unwrap().lines[0].spans[0]
+                .content
+                .to_string()
+        };
+        assert_eq!(tooltip(&plain), "<i>y</i>");
+        assert_eq!(tooltip(&styled), "y");
+    }
+}
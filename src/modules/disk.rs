@@ -0,0 +1,204 @@
+use super::{Module, ModuleConfig, ModulePosition};
+use crate::markup;
+use crate::styles;
+use crate::units::format_bytes;
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use sysinfo::Disks;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DiskOptions {
+    // mount points, each rendered with the module format
+    pub paths: Vec<String>,
+    // percentage used
+    pub warning: f64,
+    pub critical: f64,
+    // mount point -> its own thresholds, e.g. a /boot that is always full
+    pub thresholds: HashMap<String, Thresholds>,
+}
+
+// either one left out falls back to the module-wide value
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Thresholds {
+    pub warning: Option<f64>,
+    pub critical: Option<f64>,
+}
+
+impl Default for DiskOptions {
+    fn default() -> Self {
+        Self {
+            paths: vec!["/".to_string()],
+            warning: 80.0,
+            critical: 90.0,
+            thresholds: HashMap::new(),
+        }
+    }
+}
+
+struct MountUsage {
+    path: String,
+    total: u64,
+    available: u64,
+}
+
+impl MountUsage {
+    fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    fn percentage(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.used() as f64 / self.total as f64 * 100.0
+    }
+}
+
+pub struct DiskModule {
+    mounts: Vec<MountUsage>,
+    config: ModuleConfig,
+    options: DiskOptions,
+    icon: &'static str,
+}
+
+impl DiskModule {
+    pub fn new(config: ModuleConfig) -> Self {
+        Self {
+            mounts: Vec::new(),
+            options: config.options(),
+            config,
+            icon: "󰋊 ",
+        }
+    }
+
+    // (warning, critical) for the mount
+    fn thresholds(&self, path: &str) -> (f64, f64) {
+        let own = self.options.thresholds.get(path);
+        (
+            own.and_then(|t| t.warning).unwrap_or(self.options.warning),
+            own.and_then(|t| t.critical)
+                .unwrap_or(self.options.critical),
+        )
+    }
+
+    fn style(&self, mount: &MountUsage) -> Style {
+        let (warning, critical) = self.thresholds(&mount.path);
+        styles::disk_style(mount.percentage(), warning, critical)
+    }
+
+    fn mount_line(&self, mount: &MountUsage) -> Line<'static> {
+        let format = self
+            .config
+            .format
+            .as_deref()
+            .unwrap_or("{icon} {percentage}%");
+
+        markup::render(format, self.style(mount), |text| {
+            text.replace("{icon}", self.icon)
+                .replace("{path}", &mount.path)
+                .replace("{used}", &format_bytes(mount.used() as f64))
+                .replace("{free}", &format_bytes(mount.available as f64))
+                .replace("{total}", &format_bytes(mount.total as f64))
+                .replace("{percentage}", &format!("{:.0}", mount.percentage()))
+        })
+    }
+}
+
+impl Module for DiskModule {
+    fn name(&self) -> &str {
+        "disk"
+    }
+
+    fn position(&self) -> ModulePosition {
+        self.config.position.clone()
+    }
+
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        // mounts come and go, so the list is rebuilt each time
+        let disks = Disks::new_with_refreshed_list();
+
+        self.mounts = self
+            .options
+            .paths
+            .iter()
+            .filter_map(|path| {
+                disks
+                    .iter()
+                    .find(|d| d.mount_point().to_str() == Some(path.as_str()))
+                    .map(|d| MountUsage {
+                        path: path.clone(),
+                        total: d.total_space(),
+                        available: d.available_space(),
+                    })
+            })
+            .collect();
+        Ok(())
+    }
+
+    fn render(&self) -> Line<'_> {
+        let mut spans = Vec::new();
+
+        for (i, mount) in self.mounts.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(" "));
+            }
+            spans.extend(self.mount_line(mount).spans);
+        }
+        Line::from(spans)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::config_with;
+
+    #[test]
+    fn thresholds_per_mount() {
+        let module = DiskModule::new(config_with(serde_json::json!({
+            "warning": 70.0,
+            "thresholds": {
+                "/boot": { "warning": 95.0, "critical": 99.0 },
+                "/home": { "critical": 80.0 },
+            },
+        })));
+
+        assert_eq!(module.thresholds("/"), (70.0, 90.0));
+        assert_eq!(module.thresholds("/boot"), (95.0, 99.0));
+        assert_eq!(module.thresholds("/home"), (70.0, 80.0));
+    }
+
+    #[test]
+    fn style_follows_the_mount() {
+        let module = DiskModule::new(config_with(serde_json::json!({
+            "thresholds": { "/boot": { "warning": 95.0, "critical": 99.0 } },
+        })));
+        let mount = |path: &str| MountUsage {
+            path: path.to_string(),
+            total: 100,
+            available: 8,
+        };
+
+        let critical = styles::disk_style(100.0, 80.0, 90.0);
+        let normal = styles::disk_style(0.0, 80.0, 90.0);
+
+        // 92% used is past the default critical, but not /boot's warning
+        assert_eq!(module.style(&mount("/")), critical);
+        assert_eq!(module.style(&mount("/boot")), normal);
+    }
+}
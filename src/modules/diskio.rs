@@ -0,0 +1,189 @@
+use super::{Module, ModuleConfig, ModulePosition};
+use crate::markup;
+use crate::styles;
+use crate::units::format_speed;
+use ratatui::text::Line;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+// /proc/diskstats always counts 512-byte sectors
+const SECTOR_SIZE: u64 = 512;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DiskIoOptions {
+    // e.g. "nvme0n1"; all physical disks when unset
+    pub device: Option<String>,
+    // point this at a fake tree to test without real hardware
+    pub sysfs_root: PathBuf,
+}
+
+impl Default for DiskIoOptions {
+    fn default() -> Self {
+        Self {
+            device: None,
+            sysfs_root: PathBuf::from("/sys"),
+        }
+    }
+}
+
+pub struct DiskIoModule {
+    // bytes per second
+    read_speed: f64,
+    write_speed: f64,
+    // (bytes read, bytes written, when) from the previous sample
+    prev: Option<(u64, u64, Instant)>,
+    config: ModuleConfig,
+    options: DiskIoOptions,
+    icon: &'static str,
+}
+
+impl DiskIoModule {
+    pub fn new(config: ModuleConfig) -> Self {
+        Self {
+            read_speed: 0.0,
+            write_speed: 0.0,
+            prev: None,
+            options: config.options(),
+            config,
+            icon: "󰋊 ",
+        }
+    }
+
+    fn wanted(&self, name: &str) -> bool {
+        match &self.options.device {
+            Some(device) => name == device,
+            // only disks backed by hardware: partitions, dm-crypt/LVM, md
+            // RAID and zram would count the same traffic again, and loop
+            // and ram devices aren't disks at all
+            None => self
+                .options
+                .sysfs_root
+                .join("block")
+                .join(name)
+                .join("device")
+                .exists(),
+        }
+    }
+
+    fn read_counters(&self) -> Result<(u64, u64), Box<dyn Error>> {
+        let stats = fs::read_to_string("/proc/diskstats")?;
+        Ok(self.sum_counters(&stats))
+    }
+
+    // total (bytes read, bytes written) for the selected devices
+    fn sum_counters(&self, stats: &str) -> (u64, u64) {
+        let mut read = 0;
+        let mut written = 0;
+
+        for line in stats.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || !self.wanted(fields[2]) {
+                continue;
+            }
+            read += fields[5].parse::<u64>().unwrap_or(0) * SECTOR_SIZE;
+            written += fields[9].parse::<u64>().unwrap_or(0) * SECTOR_SIZE;
+        }
+        (read, written)
+    }
+}
+
+impl Module for DiskIoModule {
+    fn name(&self) -> &str {
+        "diskio"
+    }
+
+    fn position(&self) -> ModulePosition {
+        self.config.position.clone()
+    }
+
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let (read, written) = self.read_counters()?;
+        let now = Instant::now();
+
+        if let Some((prev_read, prev_written, then)) = self.prev {
+            let elapsed = now.duration_since(then).as_secs_f64();
+            if elapsed > 0.0 {
+                self.read_speed = read.saturating_sub(prev_read) as f64 / elapsed;
+                self.write_speed = written.saturating_sub(prev_written) as f64 / elapsed;
+            }
+        }
+        self.prev = Some((read, written, now));
+        Ok(())
+    }
+
+    fn render(&self) -> Line<'_> {
+        let format = self
+            .config
+            .format
+            .as_deref()
+            .unwrap_or("{icon} {read} {write}");
+
+        markup::render(format, styles::disk_io_style(), |text| {
+            text.replace("{icon}", self.icon)
+                .replace("{read}", &format_speed(self.read_speed))
+                .replace("{write}", &format_speed(self.write_speed))
+                .replace("{device}", self.options.device.as_deref().unwrap_or("all"))
+        })
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::{FakeSysfs, config_with};
+
+    // an NVMe disk with a partition opened through dm-crypt, plus a loop
+    // device and zram swap
+    const DISKSTATS: &str = "\
+ 259       0 nvme0n1 100 0 2000 0 50 0 1000 0 0 0 0
+ 259       1 nvme0n1p1 100 0 2000 0 50 0 1000 0 0 0 0
+ 253       0 dm-0 90 0 1800 0 40 0 900 0 0 0 0
+   7       0 loop0 10 0 300 0 0 0 0 0 0 0 0
+ 252       0 zram0 20 0 160 0 20 0 160 0 0 0 0
+";
+
+    fn module(sysfs: &FakeSysfs, device: Option<&str>) -> DiskIoModule {
+        DiskIoModule::new(config_with(serde_json::json!({
+            "device": device,
+            "sysfs_root": sysfs.0,
+        })))
+    }
+
+    fn fake_sysfs(name: &str) -> FakeSysfs {
+        let sysfs = FakeSysfs::new("diskio", name);
+        sysfs
+            .write("block/nvme0n1/device/model", "Fake NVMe\n")
+            .write("block/dm-0/dm/name", "root\n")
+            .write("block/loop0/loop/backing_file", "/tmp/image\n")
+            .write("block/zram0/comp_algorithm", "zstd\n");
+        sysfs
+    }
+
+    #[test]
+    fn counts_each_physical_disk_once() {
+        let sysfs = fake_sysfs("physical");
+        let module = module(&sysfs, None);
+
+        assert_eq!(module.sum_counters(DISKSTATS), (2000 * 512, 1000 * 512));
+    }
+
+    #[test]
+    fn configured_device_is_counted_alone() {
+        let sysfs = fake_sysfs("device");
+        let module = module(&sysfs, Some("dm-0"));
+
+        assert_eq!(module.sum_counters(DISKSTATS), (1800 * 512, 900 * 512));
+    }
+}
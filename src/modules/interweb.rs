@@ -1,106 +1,395 @@
-use super::{Module, ModuleConfig, ModulePosition};
+use super::{Module, ModuleConfig, ModulePosition, tooltip_line};
+use crate::markup;
+use crate::net;
 use crate::styles;
-use ratatui::{style::Style, text::Span};
+use crate::units::{RateUnit, format_rate};
+use ratatui::text::{Line, Text};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
-use std::fs::{self, read_to_string};
-use std::process::Command;
-use sysinfo::System;
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use std::time::Instant;
+
+const NET_ROOT: &str = "/sys/class/net";
+
+// name prefixes of interfaces that carry a VPN or some other tunnel
+const TUNNEL_PREFIXES: [&str; 6] = ["tun", "tap", "wg", "ppp", "vpn", "tailscale"];
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NetworkOptions {
+    // interface name or glob ("wlp*"); the default route's interface if unset
+    pub interface: Option<String>,
+    // sum traffic over every physical interface (those matching `interface`,
+    // if set)
+    pub aggregate: bool,
+    // 0 shows the raw rate; closer to 1 weighs the previous rate more (EWMA)
+    pub smoothing: f64,
+    pub units: RateUnit,
+    // used instead of `format` while on ethernet / without a link
+    pub format_ethernet: Option<String>,
+    pub format_disconnected: Option<String>,
+    // signal tiers, weakest first
+    pub wifi_icons: Vec<String>,
+    pub ethernet_icon: String,
+    pub vpn_icon: String,
+    pub disconnected_icon: String,
+    // link is up but no address has been assigned yet
+    pub no_ip_icon: String,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        Self {
+            interface: None,
+            aggregate: false,
+            smoothing: 0.0,
+            units: RateUnit::Bytes,
+            format_ethernet: None,
+            format_disconnected: None,
+            wifi_icons: vec![
+                "󰤯".to_string(),
+                "󰤟".to_string(),
+                "󰤢".to_string(),
+                "󰤥".to_string(),
+                "󰤨".to_string(),
+            ],
+            ethernet_icon: "󰈀".to_string(),
+            vpn_icon: "󰖂".to_string(),
+            disconnected_icon: "󰖪".to_string(),
+            no_ip_icon: "󰪎".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LinkState {
+    Disconnected,
+    NoAddress,
+    Connected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InterfaceKind {
+    Ethernet,
+    Wifi,
+    Tunnel,
+}
+
+impl InterfaceKind {
+    fn detect(interface: &str) -> Self {
+        let dir = Path::new(NET_ROOT).join(interface);
+        // ARPHRD_PPP, ARPHRD_TUNNEL, ARPHRD_TUNNEL6 and ARPHRD_NONE (tun, wireguard)
+        let tunnel_type = fs::read_to_string(dir.join("type"))
+            .is_ok_and(|t| matches!(t.trim(), "512" | "768" | "769" | "65534"));
+
+        if dir.join("wireless").exists() || dir.join("phy80211").exists() {
+            InterfaceKind::Wifi
+        } else if tunnel_type || TUNNEL_PREFIXES.iter().any(|p| interface.starts_with(p)) {
+            InterfaceKind::Tunnel
+        } else {
+            InterfaceKind::Ethernet
+        }
+    }
+}
+
+// '*' matches any run of characters, '?' exactly one
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob_match(&pattern[1..], name) || (!name.is_empty() && glob_match(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => glob_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+// every interface except loopback, sorted by name
+fn list_interfaces() -> Vec<String> {
+    let mut interfaces: Vec<String> = fs::read_dir(NET_ROOT)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|name| name != "lo")
+                .collect()
+        })
+        .unwrap_or_default();
+    interfaces.sort();
+    interfaces
+}
+
+// interface of the lowest-metric default route in /proc/net/route
+fn default_route_interface() -> Option<String> {
+    let routes = fs::read_to_string("/proc/net/route").ok()?;
+
+    routes
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (iface, destination, flags, metric, mask) = (
+                fields.first()?,
+                fields.get(1)?,
+                fields.get(3)?,
+                fields.get(6)?,
+                fields.get(7)?,
+            );
+            let up = u32::from_str_radix(flags, 16).ok()? & 0x1 != 0;
+
+            (up && *destination == "00000000" && *mask == "00000000")
+                .then(|| (metric.parse::<u32>().unwrap_or(u32::MAX), iface.to_string()))
+        })
+        .min()
+        .map(|(_, iface)| iface)
+}
+
+fn read_attribute(interface: &str, name: &str) -> Option<String> {
+    fs::read_to_string(Path::new(NET_ROOT).join(interface).join(name))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+// backed by a device rather than a bridge, veth or tunnel, whose traffic
+// also crosses a physical NIC
+fn is_physical(interface: &str) -> bool {
+    Path::new(NET_ROOT).join(interface).join("device").exists()
+}
+
+fn is_up(interface: &str) -> bool {
+    read_attribute(interface, "operstate").as_deref() == Some("up")
+}
+
+// tun and wireguard devices never leave "unknown", so trust the carrier there
+fn has_link(interface: &str) -> bool {
+    let state = read_attribute(interface, "operstate");
+    matches!(state.as_deref(), Some("up" | "unknown"))
+        && read_attribute(interface, "carrier").as_deref() == Some("1")
+}
+
+// signal in dBm mapped onto 0-100%, -100 dBm and below being 0
+fn signal_percentage(dbm: i32) -> u32 {
+    ((dbm + 100) * 2).clamp(0, 100) as u32
+}
 
 pub struct WebModule {
     connected: bool,
     interface: String,
+    kind: InterfaceKind,
     ssid: String,
-    download_speed: f64,
-    upload_speed: f64,
-    prev_rx_bytes: u64,
-    prev_tx_bytes: u64,
+    // dBm
+    signal: Option<i32>,
+    // MHz
+    frequency: Option<u32>,
+    ipv4: Option<Ipv4Addr>,
+    ipv6: Option<Ipv6Addr>,
+    // (download, upload) in bytes/s, none until there are two samples
+    rates: Option<(f64, f64)>,
+    // (rx bytes, tx bytes, when they were read)
+    prev: Option<(u64, u64, Instant)>,
+    // kept open so the family id is only looked up once
+    nl80211: Option<net::Nl80211>,
     config: ModuleConfig,
+    options: NetworkOptions,
 }
 
 impl WebModule {
     pub fn new(config: ModuleConfig) -> Self {
         Self {
             connected: false,
-            interface: String::from("wlan0"),
+            interface: String::new(),
+            kind: InterfaceKind::Ethernet,
             ssid: String::new(),
-            download_speed: 0.0,
-            upload_speed: 0.0,
-            prev_rx_bytes: 0,
-            prev_tx_bytes: 0,
+            signal: None,
+            frequency: None,
+            ipv4: None,
+            ipv6: None,
+            rates: None,
+            prev: None,
+            nl80211: None,
+            options: config.options(),
             config,
         }
     }
 
-    fn get_icon(&self) -> &'static str {
-        if self.connected { "󰖟 " } else { "󰖪 " }
+    fn link_state(&self) -> LinkState {
+        // every interface that is up gets a link-local IPv6 address on its own
+        let routable_v6 = self.ipv6.is_some_and(|ip| !net::is_link_local(&ip));
+
+        if !self.connected {
+            LinkState::Disconnected
+        } else if self.ipv4.is_none() && !routable_v6 {
+            LinkState::NoAddress
+        } else {
+            LinkState::Connected
+        }
     }
 
-    fn check_connection(&mut self) -> Result<(), Box<dyn Error>> {
-        // Gracefully handle command failure
-        match Command::new("ip")
-            .args(&["link", "show", &self.interface])
-            .output()
-        {
-            Ok(output) if output.status.success() => {
-                let status = String::from_utf8_lossy(&output.stdout);
-                self.connected = status.contains("state UP");
-            }
-            _ => {
-                self.connected = false;
-                self.ssid.clear();
-                return Ok(()); // Don't propagate error
+    fn wifi_icon(&self) -> &str {
+        let icons = &self.options.wifi_icons;
+        let Some(strongest) = icons.last() else {
+            return "";
+        };
+
+        match self.signal {
+            Some(dbm) => {
+                let tier = signal_percentage(dbm) as usize * icons.len() / 101;
+                &icons[tier.min(icons.len() - 1)]
             }
+            None => strongest,
         }
+    }
 
-        if self.connected {
-            match Command::new("iwgetid").args(&["-r"]).output() {
-                Ok(output) if output.status.success() => {
-                    self.ssid = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                }
-                _ => {
-                    self.ssid = String::from("Unknown");
-                }
-            }
-        } else {
-            self.ssid.clear();
+    fn get_icon(&self) -> &str {
+        match self.link_state() {
+            LinkState::Disconnected => &self.options.disconnected_icon,
+            LinkState::NoAddress => &self.options.no_ip_icon,
+            LinkState::Connected => match self.kind {
+                InterfaceKind::Wifi => self.wifi_icon(),
+                InterfaceKind::Ethernet => &self.options.ethernet_icon,
+                InterfaceKind::Tunnel => &self.options.vpn_icon,
+            },
         }
-        Ok(())
     }
 
-    fn calculate_speeds(&mut self) -> Result<(), Box<dyn Error>> {
-        let rx_path = format!("/sys/class/net/{}/statistics/rx_bytes", self.interface);
-        let tx_path = format!("/sys/class/net/{}/statistics/tx_bytes", self.interface);
+    fn format(&self) -> &str {
+        let specific = match (self.link_state(), self.kind) {
+            (LinkState::Disconnected, _) => self.options.format_disconnected.as_deref(),
+            (_, InterfaceKind::Ethernet) => self.options.format_ethernet.as_deref(),
+            _ => None,
+        };
+
+        specific
+            .or(self.config.format.as_deref())
+            .unwrap_or("{icon} {ssid}")
+    }
 
-        // Safely read files, don't crash if they don't exist
-        let rx_bytes = fs::read_to_string(&rx_path)
-            .ok()
-            .and_then(|s| s.trim().parse::<u64>().ok())
-            .unwrap_or(0);
+    fn matching_interfaces(&self) -> Vec<String> {
+        let interfaces = list_interfaces();
+        match &self.options.interface {
+            Some(pattern) => interfaces
+                .into_iter()
+                .filter(|name| glob_match(pattern.as_bytes(), name.as_bytes()))
+                .collect(),
+            None => interfaces,
+        }
+    }
 
-        let tx_bytes = fs::read_to_string(&tx_path)
-            .ok()
-            .and_then(|s| s.trim().parse::<u64>().ok())
-            .unwrap_or(0);
+    // the default route's interface, unless the configured one rules it out;
+    // then the first matching interface that is up
+    fn resolve_interface(&self) -> String {
+        let default = default_route_interface();
+        if self.options.interface.is_none() {
+            return default.unwrap_or_default();
+        }
 
-        if self.prev_rx_bytes > 0 {
-            let rx_diff = rx_bytes.saturating_sub(self.prev_rx_bytes);
-            let tx_diff = tx_bytes.saturating_sub(self.prev_tx_bytes);
+        let candidates = self.matching_interfaces();
+        default
+            .filter(|d| candidates.contains(d))
+            .or_else(|| candidates.iter().find(|name| is_up(name)).cloned())
+            .or_else(|| candidates.first().cloned())
+            .unwrap_or_default()
+    }
 
-            self.download_speed = (rx_diff as f64) / 1024.0 / 2.0;
-            self.upload_speed = (tx_diff as f64) / 1024.0 / 2.0;
+    fn select_interface(&mut self) {
+        let interface = self.resolve_interface();
+        if interface == self.interface {
+            return;
         }
-        self.prev_rx_bytes = rx_bytes;
-        self.prev_tx_bytes = tx_bytes;
 
-        Ok(())
+        self.kind = InterfaceKind::detect(&interface);
+        self.interface = interface;
+        // another interface's counters would read as a huge jump
+        if !self.options.aggregate {
+            self.prev = None;
+            self.rates = None;
+        }
     }
 
-    fn format_speed(speed: f64) -> String {
-        if speed >= 1024.0 {
-            format!("{:.1} MB/s", speed / 1024.0)
+    fn read_link(&mut self) {
+        self.connected = !self.interface.is_empty() && has_link(&self.interface);
+
+        let wifi = if self.connected && self.kind == InterfaceKind::Wifi {
+            net::wifi_info(&mut self.nl80211, &self.interface)
         } else {
-            format!("{:.0} KB/s", speed)
+            net::WifiInfo::default()
+        };
+        self.ssid = wifi.ssid;
+        self.signal = wifi.signal;
+        self.frequency = wifi.frequency;
+
+        (self.ipv4, self.ipv6) = if self.connected {
+            net::addresses(&self.interface)
+        } else {
+            (None, None)
+        };
+    }
+
+    fn read_counters(interface: &str) -> Option<(u64, u64)> {
+        let stats = Path::new(NET_ROOT).join(interface).join("statistics");
+        let read = |name: &str| -> Option<u64> {
+            fs::read_to_string(stats.join(name))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        };
+        read("rx_bytes").zip(read("tx_bytes"))
+    }
+
+    // (rx bytes, tx bytes) for the selected interface, or summed over all
+    fn counters(&self) -> Option<(u64, u64)> {
+        if !self.options.aggregate {
+            return Self::read_counters(&self.interface);
         }
+
+        let interfaces = self.matching_interfaces();
+        interfaces
+            .iter()
+            .filter(|name| is_physical(name))
+            .filter_map(|name| Self::read_counters(name))
+            .reduce(|(rx, tx), (r, t)| (rx + r, tx + t))
+    }
+
+    fn smooth(&self, prev: f64, sample: f64) -> f64 {
+        let weight = self.options.smoothing.clamp(0.0, 1.0);
+        prev * weight + sample * (1.0 - weight)
+    }
+
+    fn calculate_speeds(&mut self) -> Result<(), Box<dyn Error>> {
+        let now = Instant::now();
+        let counters = self.counters();
+
+        self.rates = match (counters, self.prev) {
+            // counters going backwards means the interface was reset or
+            // re-created (or, aggregated, one went away), so start over
+            (Some((rx, tx)), Some((prev_rx, prev_tx, prev_time)))
+                if rx >= prev_rx && tx >= prev_tx =>
+            {
+                let elapsed = now.duration_since(prev_time).as_secs_f64();
+                if elapsed > 0.0 {
+                    let download = (rx - prev_rx) as f64 / elapsed;
+                    let upload = (tx - prev_tx) as f64 / elapsed;
+
+                    Some(match self.rates {
+                        Some((prev_down, prev_up)) => (
+                            self.smooth(prev_down, download),
+                            self.smooth(prev_up, upload),
+                        ),
+                        None => (download, upload),
+                    })
+                } else {
+                    self.rates
+                }
+            }
+            _ => None,
+        };
+        self.prev = counters.map(|(rx, tx)| (rx, tx, now));
+
+        Ok(())
     }
 }
 
@@ -114,28 +403,104 @@ impl Module for WebModule {
     }
 
     fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        self.select_interface();
+        self.read_link();
+
         // Don't propagate errors - just log them
-        if let Err(e) = self.check_connection() {
-            eprintln!("Network check failed: {}", e);
-        }
         if let Err(e) = self.calculate_speeds() {
             eprintln!("Speed calculation failed: {}", e);
         }
         Ok(())
     }
 
-    fn render(&self) -> Span {
-        let format = self.config.format.as_deref().unwrap_or("{icon} {ssid}");
+    fn render(&self) -> Line<'_> {
+        let format = self.format();
         let icon = self.get_icon();
+        let (download, upload) = self.rates.unwrap_or_default();
+
+        markup::render(
+            format,
+            styles::network_style(self.link_state() == LinkState::Connected),
+            |text| {
+                text.replace("{icon}", icon)
+                    .replace("{ssid}", &self.ssid)
+                    .replace("{download}", &format_rate(download, self.options.units))
+                    .replace("{upload}", &format_rate(upload, self.options.units))
+                    .replace("{interface}", &self.interface)
+                    .replace(
+                        "{signal}",
+                        &self
+                            .signal
+                            .map(|dbm| signal_percentage(dbm).to_string())
+                            .unwrap_or_default(),
+                    )
+                    .replace(
+                        "{signal_dbm}",
+                        &self.signal.map(|dbm| dbm.to_string()).unwrap_or_default(),
+                    )
+                    .replace(
+                        "{frequency}",
+                        &self
+                            .frequency
+                            .map(|mhz| format!("{:.1}", mhz as f64 / 1000.0))
+                            .unwrap_or_default(),
+                    )
+                    .replace(
+                        "{ipaddr}",
+                        &self.ipv4.map(|ip| ip.to_string()).unwrap_or_default(),
+                    )
+                    .replace(
+                        "{ipv6}",
+                        &self.ipv6.map(|ip| ip.to_string()).unwrap_or_default(),
+                    )
+            },
+        )
+    }
 
-        let text = format
-            .replace("{icon}", icon)
-            .replace("{ssid}", &self.ssid)
-            .replace("{download}", &Self::format_speed(self.download_speed))
-            .replace("{upload}", &Self::format_speed(self.upload_speed))
-            .replace("{interface}", &self.interface);
+    fn tooltip(&self) -> Option<Text<'_>> {
+        let kind = match self.kind {
+            InterfaceKind::Wifi => "Wi-Fi",
+            InterfaceKind::Ethernet => "Ethernet",
+            InterfaceKind::Tunnel => "VPN",
+        };
+        let state = match self.link_state() {
+            LinkState::Disconnected => "Disconnected",
+            LinkState::NoAddress => "No address",
+            LinkState::Connected => "Connected",
+        };
+        let (download, upload) = self.rates.unwrap_or_default();
 
-        Span::styled(text, styles::network_style())
+        let mut lines = vec![
+            tooltip_line("Interface", format!("{} ({})", self.interface, kind)),
+            tooltip_line("State", state),
+        ];
+        if !self.ssid.is_empty() {
+            lines.push(tooltip_line("SSID", self.ssid.clone()));
+        }
+        if let Some(dbm) = self.signal {
+            lines.push(tooltip_line(
+                "Signal",
+                format!("{}% ({} dBm)", signal_percentage(dbm), dbm),
+            ));
+        }
+        if let Some(mhz) = self.frequency {
+            lines.push(tooltip_line(
+                "Frequency",
+                format!("{:.1} GHz", mhz as f64 / 1000.0),
+            ));
+        }
+        if let Some(ip) = self.ipv4 {
+            lines.push(tooltip_line("IPv4", ip.to_string()));
+        }
+        if let Some(ip) = self.ipv6 {
+            lines.push(tooltip_line("IPv6", ip.to_string()));
+        }
+        lines.push(tooltip_line(
+            "Down",
+            format_rate(download, self.options.units),
+        ));
+        lines.push(tooltip_line("Up", format_rate(upload, self.options.units)));
+        Some(Text::from(lines))
     }
 
     fn on_click(&mut self, x: u16, y: u16) -> Result<(), Box<dyn Error>> {
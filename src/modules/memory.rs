@@ -1,15 +1,42 @@
 use super::{Module, ModuleConfig, ModulePosition};
+use crate::markup;
 use crate::styles;
-use ratatui::{style::Style, text::Span};
+use crate::units::{UnitSystem, format_size};
+use ratatui::text::Line;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::fs;
 use sysinfo::System;
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MemoryOptions {
+    pub units: UnitSystem,
+    // decimals shown for sizes
+    pub precision: usize,
+}
+
+impl Default for MemoryOptions {
+    fn default() -> Self {
+        Self {
+            units: UnitSystem::Iec,
+            precision: 1,
+        }
+    }
+}
+
 pub struct MemoryModule {
     usage: f64,
-    used_gb: f64,
-    total_gb: f64,
+    // all in bytes
+    used: u64,
+    total: u64,
+    available: u64,
+    cached: u64,
+    swap_used: u64,
+    swap_total: u64,
     system: System,
     config: ModuleConfig,
+    options: MemoryOptions,
     icon: &'static str,
 }
 
@@ -17,13 +44,46 @@ impl MemoryModule {
     pub fn new(config: ModuleConfig) -> Self {
         Self {
             usage: 0.0,
-            used_gb: 0.0,
-            total_gb: 0.0,
+            used: 0,
+            total: 0,
+            available: 0,
+            cached: 0,
+            swap_used: 0,
+            swap_total: 0,
             system: System::new_all(),
+            options: config.options(),
             config,
             icon: "󰍛 ",
         }
     }
+
+    fn size(&self, bytes: u64) -> String {
+        format_size(bytes as f64, self.options.units, self.options.precision)
+    }
+
+    fn swap_percentage(&self) -> f64 {
+        if self.swap_total == 0 {
+            return 0.0;
+        }
+        self.swap_used as f64 / self.swap_total as f64 * 100.0
+    }
+}
+
+// Page cache the way htop counts it: Cached + SReclaimable - Shmem.
+// sysinfo doesn't expose it, so it comes straight from /proc/meminfo
+fn read_cached() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let field = |name: &str| -> u64 {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|kb| kb.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+
+    let cached = field("Cached") + field("SReclaimable");
+    Some(cached.saturating_sub(field("Shmem")) * 1024)
 }
 
 impl Module for MemoryModule {
@@ -43,22 +103,33 @@ impl Module for MemoryModule {
 
         if total > 0.0 {
             self.usage = ((used / total * 100.0) * 10.0).round() / 10.0;
-            self.used_gb = used / 1_073_741_824.0;
-            self.total_gb = total / 1_073_741_824.0;
         }
+        self.used = self.system.used_memory();
+        self.total = self.system.total_memory();
+        self.available = self.system.available_memory();
+        self.swap_used = self.system.used_swap();
+        self.swap_total = self.system.total_swap();
+        self.cached = read_cached().unwrap_or(0);
         Ok(())
     }
 
-    fn render(&self) -> Span {
+    fn render(&self) -> Line<'_> {
         let format = self.config.format.as_deref().unwrap_or("{icon} {usage}%");
 
-        let text = format
-            .replace("{icon}", self.icon)
-            .replace("{usage}", &format!("{:.0}", self.usage))
-            .replace("{used}", &format!("{:.1}", self.used_gb))
-            .replace("{total}", &format!("{:.1}", self.total_gb));
-
-        Span::styled(text, styles::memory_style())
+        markup::render(format, styles::memory_style(), |text| {
+            text.replace("{icon}", self.icon)
+                .replace("{usage}", &format!("{:.0}", self.usage))
+                .replace("{used}", &self.size(self.used))
+                .replace("{total}", &self.size(self.total))
+                .replace("{avail}", &self.size(self.available))
+                .replace("{cached}", &self.size(self.cached))
+                .replace("{swap_used}", &self.size(self.swap_used))
+                .replace("{swap_total}", &self.size(self.swap_total))
+                .replace(
+                    "{swap_percentage}",
+                    &format!("{:.0}", self.swap_percentage()),
+                )
+        })
     }
 
     fn on_click(&mut self, _x: u16, _y: u16) -> Result<(), Box<dyn Error>> {
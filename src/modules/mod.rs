@@ -1,12 +1,18 @@
-use ratatui::{style::Style, text::Span};
-use serde::{Deserialize, Serialize};
-use std::{any::Any, borrow::Borrow, error::Error};
+use crate::styles;
+use ratatui::text::{Line, Span, Text};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{any::Any, collections::HashMap, error::Error};
 
 pub mod battery;
 pub mod clock;
 pub mod cpu;
+pub mod custom;
+pub mod disk;
+pub mod diskio;
 pub mod interweb;
 pub mod memory;
+pub mod monitor;
+pub mod temperature;
 pub mod window;
 pub mod workspaces;
 
@@ -16,6 +22,24 @@ pub struct ModuleConfig {
     pub format: Option<String>,
     pub interval: Option<u64>,
     pub position: ModulePosition,
+    // module-specific keys, read back through `options()`
+    #[serde(flatten, default)]
+    pub options: HashMap<String, serde_json::Value>,
+}
+
+impl ModuleConfig {
+    pub fn options<T: DeserializeOwned + Default>(&self) -> T {
+        let map: serde_json::Map<String, serde_json::Value> = self
+            .options
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        serde_json::from_value(serde_json::Value::Object(map)).unwrap_or_else(|e| {
+            eprintln!("Invalid module options: {}", e);
+            T::default()
+        })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -34,22 +58,109 @@ impl Default for ModuleConfig {
             format: None,
             interval: None,
             position: ModulePosition::Right,
+            options: HashMap::new(),
         }
     }
 }
 
+// "label   value" row for tooltips
+pub fn tooltip_line(label: &str, value: impl Into<String>) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{:<10}", label), styles::tooltip_label_style()),
+        Span::styled(value.into(), styles::tooltip_value_style()),
+    ])
+}
+
+// window class (lowercase) -> glyph, the default for every module that
+// shows an icon per window
+pub fn default_class_icons() -> HashMap<String, String> {
+    HashMap::from([
+        ("firefox".to_string(), "󰈹".to_string()),
+        ("kitty".to_string(), "󰆍".to_string()),
+        ("code".to_string(), "󰨞".to_string()),
+        ("discord".to_string(), "󰙯".to_string()),
+        ("spotify".to_string(), "󰓇".to_string()),
+    ])
+}
+
+// for a class with no icon of its own
+pub const DEFAULT_CLASS_ICON: &str = "󰖯";
+
 pub trait Module: Send {
     fn name(&self) -> &str;
     fn position(&self) -> ModulePosition;
     fn update(&mut self) -> Result<(), Box<dyn Error>>;
-    fn render(&self) -> Span;
+    // ms between updates when the config doesn't set an interval; None
+    // updates on every tick
+    fn default_interval(&self) -> Option<u64> {
+        None
+    }
+    // one line, so parts of the output can be styled separately
+    fn render(&self) -> Line<'_>;
     fn on_click(&mut self, x: u16, y: u16) -> Result<(), Box<dyn Error>> {
         Ok(())
     }
     fn on_scroll(&mut self, delta: i32) -> Result<(), Box<dyn Error>> {
         Ok(())
     }
+    // details shown on hover, or in a window of their own on right click
+    fn tooltip(&self) -> Option<Text<'_>> {
+        None
+    }
 
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
+
+// config for a module under test, with options as its module-specific keys
+#[cfg(test)]
+pub fn config_with(options: serde_json::Value) -> ModuleConfig {
+    let mut config = ModuleConfig::default();
+    if let serde_json::Value::Object(map) = options {
+        config.options.extend(map);
+    }
+    config
+}
+
+// a sysfs tree under the temp dir, removed again on drop
+#[cfg(test)]
+pub struct FakeSysfs(pub std::path::PathBuf);
+
+#[cfg(test)]
+impl FakeSysfs {
+    pub fn new(module: &str, name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!(
+            "jlessbar-{}-{}-{}",
+            module,
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        Self(root)
+    }
+
+    pub fn write(&self, file: &str, contents: &str) -> &Self {
+        let path = self.0.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+        self
+    }
+}
+
+#[cfg(test)]
+impl Drop for FakeSysfs {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// what the module draws, without the styling
+#[cfg(test)]
+pub fn render_text(module: &dyn Module) -> String {
+    module
+        .render()
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
@@ -0,0 +1,74 @@
+use super::{Module, ModuleConfig, ModulePosition};
+use crate::hyprland::HyprlandClient;
+use crate::markup;
+use crate::styles;
+use ratatui::text::Line;
+use std::error::Error;
+
+pub struct MonitorModule {
+    monitor: Option<String>,
+    focused_monitor: String,
+    config: ModuleConfig,
+}
+
+impl MonitorModule {
+    pub fn new(config: ModuleConfig, monitor: Option<String>) -> Self {
+        let focused_monitor = HyprlandClient::new()
+            .and_then(|h| h.get_focused_monitor())
+            .unwrap_or_default();
+
+        Self {
+            monitor,
+            focused_monitor,
+            config,
+        }
+    }
+
+    pub fn set_focused_monitor(&mut self, monitor: String) {
+        self.focused_monitor = monitor;
+    }
+
+    // an unbound bar just follows focus
+    fn monitor_name(&self) -> &str {
+        self.monitor.as_deref().unwrap_or(&self.focused_monitor)
+    }
+
+    fn is_focused(&self) -> bool {
+        self.monitor_name() == self.focused_monitor
+    }
+
+    fn get_icon(&self) -> &'static str {
+        if self.is_focused() { "󰍹" } else { "󰶐" }
+    }
+}
+
+impl Module for MonitorModule {
+    fn name(&self) -> &str {
+        "monitor"
+    }
+
+    fn position(&self) -> ModulePosition {
+        self.config.position.clone()
+    }
+
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn render(&self) -> Line<'_> {
+        let format = self.config.format.as_deref().unwrap_or("{icon} {name}");
+
+        markup::render(format, styles::monitor_style(self.is_focused()), |text| {
+            text.replace("{icon}", self.get_icon())
+                .replace("{name}", self.monitor_name())
+        })
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,423 @@
+use super::{Module, ModuleConfig, ModulePosition};
+use crate::markup;
+use crate::styles;
+use ratatui::text::Line;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use sysinfo::Components;
+
+// picked when no sensor is configured, in this order
+const PREFERRED_SENSORS: [&str; 4] = ["package", "tctl", "x86_pkg_temp", "cpu"];
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    fn convert(&self, celsius: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+            TemperatureUnit::Kelvin => celsius + 273.15,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+            TemperatureUnit::Kelvin => "K",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TemperatureOptions {
+    // label substring (e.g. "Package id 0") or the path of a temp file
+    pub sensor: Option<String>,
+    // point this at a fake tree to test without real hardware
+    pub sysfs_root: PathBuf,
+    pub unit: TemperatureUnit,
+    // °C; falls back to the sensor's own critical point, then 80
+    pub critical: Option<f64>,
+}
+
+impl Default for TemperatureOptions {
+    fn default() -> Self {
+        Self {
+            sensor: None,
+            sysfs_root: PathBuf::from("/sys"),
+            unit: TemperatureUnit::Celsius,
+            critical: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Sensor {
+    label: String,
+    path: PathBuf,
+    // °C
+    temp: f64,
+    critical: Option<f64>,
+}
+
+pub struct TemperatureModule {
+    sensor: Option<Sensor>,
+    // hottest reading across every sensor, °C
+    max: f64,
+    config: ModuleConfig,
+    options: TemperatureOptions,
+}
+
+impl TemperatureModule {
+    pub fn new(config: ModuleConfig) -> Self {
+        Self {
+            sensor: None,
+            max: 0.0,
+            options: config.options(),
+            config,
+        }
+    }
+
+    fn read_sensors(&self) -> Vec<Sensor> {
+        let mut sensors = read_hwmon(&self.options.sysfs_root);
+        sensors.extend(read_thermal_zones(&self.options.sysfs_root));
+
+        if sensors.is_empty() {
+            sensors = read_components();
+        }
+        sensors
+    }
+
+    fn select<'a>(&self, sensors: &'a [Sensor]) -> Option<&'a Sensor> {
+        match &self.options.sensor {
+            Some(wanted) if wanted.starts_with('/') => {
+                sensors.iter().find(|s| s.path == Path::new(wanted))
+            }
+            Some(wanted) => {
+                let wanted = wanted.to_lowercase();
+                sensors
+                    .iter()
+                    .find(|s| s.label.to_lowercase().contains(&wanted))
+            }
+            None => PREFERRED_SENSORS
+                .iter()
+                .find_map(|p| sensors.iter().find(|s| s.label.to_lowercase().contains(p)))
+                .or_else(|| sensors.first()),
+        }
+    }
+
+    fn critical_point(&self) -> f64 {
+        self.options
+            .critical
+            .or_else(|| self.sensor.as_ref().and_then(|s| s.critical))
+            .unwrap_or(80.0)
+    }
+
+    fn is_critical(&self) -> bool {
+        self.sensor
+            .as_ref()
+            .is_some_and(|s| s.temp >= self.critical_point())
+    }
+
+    fn get_icon(&self) -> &'static str {
+        let temp = self.sensor.as_ref().map(|s| s.temp).unwrap_or(0.0);
+        let ratio = temp / self.critical_point();
+
+        if ratio >= 1.0 {
+            "󰸁"
+        } else if ratio >= 0.8 {
+            "󱃂"
+        } else if ratio >= 0.5 {
+            "󰔏"
+        } else {
+            "󱃃"
+        }
+    }
+}
+
+fn read_millidegrees(path: &Path) -> Option<f64> {
+    let raw = fs::read_to_string(path).ok()?;
+    let value = raw.trim().parse::<f64>().ok()?;
+    Some(value / 1000.0)
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+// class/hwmon/hwmon*/temp*_input, labelled "<chip> <temp*_label>"
+fn read_hwmon(root: &Path) -> Vec<Sensor> {
+    let mut sensors = Vec::new();
+    let Ok(chips) = fs::read_dir(root.join("class/hwmon")) else {
+        return sensors;
+    };
+
+    for chip in chips.flatten() {
+        let chip_path = chip.path();
+        let chip_name = read_trimmed(&chip_path.join("name")).unwrap_or_default();
+        let Ok(entries) = fs::read_dir(&chip_path) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some(prefix) = file_name.strip_suffix("_input") else {
+                continue;
+            };
+            if !prefix.starts_with("temp") {
+                continue;
+            }
+            let Some(temp) = read_millidegrees(&entry.path()) else {
+                continue;
+            };
+
+            let label = read_trimmed(&chip_path.join(format!("{}_label", prefix)))
+                .unwrap_or_else(|| prefix.to_string());
+            sensors.push(Sensor {
+                label: format!("{} {}", chip_name, label).trim().to_string(),
+                path: entry.path(),
+                temp,
+                critical: read_millidegrees(&chip_path.join(format!("{}_crit", prefix))),
+            });
+        }
+    }
+
+    sensors.sort_by(|a, b| a.path.cmp(&b.path));
+    sensors
+}
+
+// class/thermal/thermal_zone*/temp, labelled by the zone's type
+fn read_thermal_zones(root: &Path) -> Vec<Sensor> {
+    let mut sensors = Vec::new();
+    let Ok(zones) = fs::read_dir(root.join("class/thermal")) else {
+        return sensors;
+    };
+
+    for zone in zones.flatten() {
+        let zone_path = zone.path();
+        if !zone
+            .file_name()
+            .to_string_lossy()
+            .starts_with("thermal_zone")
+        {
+            continue;
+        }
+        let path = zone_path.join("temp");
+        let Some(temp) = read_millidegrees(&path) else {
+            continue;
+        };
+
+        sensors.push(Sensor {
+            label: read_trimmed(&zone_path.join("type")).unwrap_or_default(),
+            path,
+            temp,
+            critical: None,
+        });
+    }
+
+    sensors.sort_by(|a, b| a.path.cmp(&b.path));
+    sensors
+}
+
+// for platforms where sysinfo knows more than sysfs does
+fn read_components() -> Vec<Sensor> {
+    Components::new_with_refreshed_list()
+        .iter()
+        .map(|c| Sensor {
+            label: c.label().to_string(),
+            path: PathBuf::new(),
+            temp: c.temperature() as f64,
+            critical: c.critical().map(|t| t as f64),
+        })
+        .collect()
+}
+
+impl Module for TemperatureModule {
+    fn name(&self) -> &str {
+        "temperature"
+    }
+
+    fn position(&self) -> ModulePosition {
+        self.config.position.clone()
+    }
+
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let sensors = self.read_sensors();
+
+        self.max = sensors.iter().map(|s| s.temp).fold(0.0, f64::max);
+        self.sensor = self.select(&sensors).cloned();
+        Ok(())
+    }
+
+    fn render(&self) -> Line<'_> {
+        let format = self
+            .config
+            .format
+            .as_deref()
+            .unwrap_or("{icon} {temp}{unit}");
+        let unit = self.options.unit;
+
+        let Some(sensor) = &self.sensor else {
+            return Line::default();
+        };
+
+        markup::render(
+            format,
+            styles::temperature_style(self.is_critical()),
+            |text| {
+                text.replace("{icon}", self.get_icon())
+                    .replace("{temp}", &format!("{:.0}", unit.convert(sensor.temp)))
+                    .replace("{max}", &format!("{:.0}", unit.convert(self.max)))
+                    .replace("{unit}", unit.symbol())
+                    .replace("{label}", &sensor.label)
+            },
+        )
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::{FakeSysfs, config_with, render_text};
+
+    fn intel_laptop(name: &str) -> FakeSysfs {
+        let sysfs = FakeSysfs::new("temperature", name);
+        sysfs
+            .write("class/hwmon/hwmon0/name", "coretemp\n")
+            .write("class/hwmon/hwmon0/temp1_input", "52000\n")
+            .write("class/hwmon/hwmon0/temp1_label", "Package id 0\n")
+            .write("class/hwmon/hwmon0/temp1_crit", "100000\n")
+            .write("class/hwmon/hwmon0/temp2_input", "48000\n")
+            .write("class/hwmon/hwmon0/temp2_label", "Core 0\n")
+            .write("class/hwmon/hwmon1/name", "nvme\n")
+            .write("class/hwmon/hwmon1/temp1_input", "38850\n")
+            .write("class/hwmon/hwmon1/temp1_label", "Composite\n")
+            .write("class/thermal/thermal_zone0/type", "acpitz\n")
+            .write("class/thermal/thermal_zone0/temp", "27800\n");
+        sysfs
+    }
+
+    fn updated(sysfs: &FakeSysfs, mut options: serde_json::Value) -> TemperatureModule {
+        options["sysfs_root"] = serde_json::json!(sysfs.0);
+
+        let mut module = TemperatureModule::new(config_with(options));
+        module.update().unwrap();
+        module
+    }
+
+    #[test]
+    fn reads_hwmon_and_thermal_zones() {
+        let sysfs = intel_laptop("read");
+        let sensors = read_hwmon(&sysfs.0);
+
+        let labels: Vec<&str> = sensors.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            ["coretemp Package id 0", "coretemp Core 0", "nvme Composite"]
+        );
+        assert_eq!(sensors[0].temp, 52.0);
+        assert_eq!(sensors[0].critical, Some(100.0));
+        assert_eq!(sensors[1].critical, None);
+
+        let zones = read_thermal_zones(&sysfs.0);
+        assert_eq!(zones.len(), 1);
+        assert_eq!(zones[0].label, "acpitz");
+        assert_eq!(zones[0].temp, 27.8);
+    }
+
+    #[test]
+    fn selects_by_label() {
+        let sysfs = intel_laptop("label");
+        let module = updated(&sysfs, serde_json::json!({ "sensor": "composite" }));
+
+        assert_eq!(module.sensor.unwrap().label, "nvme Composite");
+    }
+
+    #[test]
+    fn selects_by_path() {
+        let sysfs = intel_laptop("path");
+        let path = sysfs.0.join("class/thermal/thermal_zone0/temp");
+        let module = updated(&sysfs, serde_json::json!({ "sensor": path }));
+
+        assert_eq!(module.sensor.unwrap().label, "acpitz");
+    }
+
+    #[test]
+    fn falls_back_in_preferred_order() {
+        let sysfs = intel_laptop("preferred");
+        let module = updated(&sysfs, serde_json::json!({}));
+        assert_eq!(module.sensor.unwrap().label, "coretemp Package id 0");
+
+        // tctl comes before cpu, even from a later chip
+        let sysfs = FakeSysfs::new("temperature", "tctl");
+        sysfs
+            .write("class/hwmon/hwmon0/name", "cpu_thermal\n")
+            .write("class/hwmon/hwmon0/temp1_input", "40000\n")
+            .write("class/hwmon/hwmon1/name", "k10temp\n")
+            .write("class/hwmon/hwmon1/temp1_input", "61000\n")
+            .write("class/hwmon/hwmon1/temp1_label", "Tctl\n");
+        let module = updated(&sysfs, serde_json::json!({}));
+        assert_eq!(module.sensor.unwrap().label, "k10temp Tctl");
+
+        // nothing preferred, so the first sensor
+        let sysfs = FakeSysfs::new("temperature", "first");
+        sysfs
+            .write("class/hwmon/hwmon0/name", "nvme\n")
+            .write("class/hwmon/hwmon0/temp1_input", "35000\n")
+            .write("class/thermal/thermal_zone0/type", "acpitz\n")
+            .write("class/thermal/thermal_zone0/temp", "27800\n");
+        let module = updated(&sysfs, serde_json::json!({}));
+        assert_eq!(module.sensor.unwrap().label, "nvme temp1");
+    }
+
+    #[test]
+    fn critical_point_from_crit_file() {
+        let sysfs = intel_laptop("crit");
+        sysfs.write("class/hwmon/hwmon0/temp1_input", "100000\n");
+
+        let module = updated(&sysfs, serde_json::json!({}));
+        assert_eq!(module.critical_point(), 100.0);
+        assert!(module.is_critical());
+
+        // the option wins over the sensor's own
+        let module = updated(&sysfs, serde_json::json!({ "critical": 110.0 }));
+        assert_eq!(module.critical_point(), 110.0);
+        assert!(!module.is_critical());
+
+        // zones have no _crit, so the default
+        let path = sysfs.0.join("class/thermal/thermal_zone0/temp");
+        let module = updated(&sysfs, serde_json::json!({ "sensor": path }));
+        assert_eq!(module.critical_point(), 80.0);
+    }
+
+    #[test]
+    fn converts_units() {
+        assert_eq!(TemperatureUnit::Celsius.convert(100.0), 100.0);
+        assert_eq!(TemperatureUnit::Fahrenheit.convert(100.0), 212.0);
+        assert_eq!(TemperatureUnit::Kelvin.convert(100.0), 373.15);
+
+        let sysfs = intel_laptop("units");
+        let mut module = updated(&sysfs, serde_json::json!({ "unit": "Fahrenheit" }));
+        module.config.format = Some("{temp}{unit} {max}{unit}".to_string());
+        assert_eq!(render_text(&module), "126°F 126°F");
+
+        let module = updated(&sysfs, serde_json::json!({ "unit": "Kelvin" }));
+        assert!(render_text(&module).ends_with(" 325K"));
+    }
+}
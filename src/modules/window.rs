@@ -1,67 +1,363 @@
-use super::{Module, ModuleConfig, ModulePosition};
+use super::{DEFAULT_CLASS_ICON, Module, ModuleConfig, ModulePosition, default_class_icons};
+use crate::hyprland::{HyprlandClient, WorkspaceRef};
+use crate::hyprland_ipc::ActiveWindow;
+use crate::markup;
 use crate::styles;
-use ratatui::{style::Style, text::Span};
-use serde::{Deserialize, de};
+use ratatui::text::Line;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 use std::error::Error;
-use std::process::{Command, Output};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-#[derive(Debug, Deserialize)]
-struct ActiveWindow {
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ClientInfo {
+    address: String,
     class: String,
     title: String,
+    workspace: Option<WorkspaceRef>,
+    floating: bool,
+    #[serde(deserialize_with = "bool_or_int")]
+    fullscreen: bool,
+    pid: i32,
+}
+
+// newer Hyprland reports fullscreen as a mode number instead of a bool
+fn bool_or_int<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(value
+        .as_bool()
+        .or_else(|| value.as_i64().map(|n| n != 0))
+        .unwrap_or(false))
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum EllipsisPosition {
+    Start,
+    Middle,
+    End,
+}
+
+// Rewrites the title when both patterns match; `replace` expands the title's
+// capture groups, or the class's when there is no title pattern
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RewriteRule {
+    #[serde(default)]
+    pub class: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub replace: String,
+}
+
+struct CompiledRule {
+    class: Option<Regex>,
+    title: Option<Regex>,
+    replace: String,
+}
+
+impl CompiledRule {
+    fn compile(rule: &RewriteRule) -> Result<Self, regex::Error> {
+        Ok(Self {
+            class: rule.class.as_deref().map(Regex::new).transpose()?,
+            title: rule.title.as_deref().map(Regex::new).transpose()?,
+            replace: rule.replace.clone(),
+        })
+    }
+
+    fn apply(&self, class: &str, title: &str) -> Option<String> {
+        let class_caps = match &self.class {
+            Some(re) => Some(re.captures(class)?),
+            None => None,
+        };
+        let title_caps = match &self.title {
+            Some(re) => Some(re.captures(title)?),
+            None => None,
+        };
+
+        let mut rewritten = String::new();
+        match title_caps.or(class_caps) {
+            Some(caps) => caps.expand(&self.replace, &mut rewritten),
+            None => rewritten.push_str(&self.replace),
+        }
+        Some(rewritten)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WindowOptions {
+    // in terminal cells, not bytes
+    pub max_length: usize,
+    pub ellipsis: String,
+    pub ellipsis_position: EllipsisPosition,
+    // scroll long titles instead of cutting them
+    pub marquee: bool,
+    pub marquee_gap: String,
+    // ms per grapheme scrolled, whatever the update interval
+    pub marquee_step: u64,
+    // tried in order, first match wins
+    pub rewrite: Vec<RewriteRule>,
+    // window class (lowercase) -> glyph, for {icon}
+    pub class_icons: HashMap<String, String>,
+    pub default_icon: String,
+    // shown by {floating} / {fullscreen} when the window is in that state
+    pub floating_icon: String,
+    pub fullscreen_icon: String,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        Self {
+            max_length: 50,
+            ellipsis: "…".to_string(),
+            ellipsis_position: EllipsisPosition::End,
+            marquee: false,
+            marquee_gap: "   ".to_string(),
+            marquee_step: 300,
+            rewrite: vec![
+                RewriteRule {
+                    class: None,
+                    title: Some("^(.*) — Mozilla Firefox$".to_string()),
+                    replace: "$1".to_string(),
+                },
+                RewriteRule {
+                    class: None,
+                    title: Some("^(.*) - Visual Studio Code$".to_string()),
+                    replace: "$1".to_string(),
+                },
+            ],
+            class_icons: default_class_icons(),
+            default_icon: DEFAULT_CLASS_ICON.to_string(),
+            floating_icon: "󰖲".to_string(),
+            fullscreen_icon: "󰊓".to_string(),
+        }
+    }
 }
 
 pub struct WindowModule {
     title: String,
     class: String,
+    address: String,
+    workspace: String,
+    floating: bool,
+    fullscreen: bool,
+    pid: i32,
     config: ModuleConfig,
-    max_length: usize,
+    options: WindowOptions,
+    rules: Vec<CompiledRule>,
+    // marquee position, in graphemes
+    scroll_offset: usize,
+    last_step: Instant,
+    monitor: Option<String>,
+    focused_monitor: String,
 }
 
 impl WindowModule {
-    pub fn new(config: ModuleConfig) -> Self {
+    pub fn new(config: ModuleConfig, monitor: Option<String>) -> Self {
+        let focused_monitor = match monitor {
+            Some(_) => HyprlandClient::new()
+                .and_then(|h| h.get_focused_monitor())
+                .unwrap_or_default(),
+            None => String::new(),
+        };
+
+        let options: WindowOptions = config.options();
+        let rules = options
+            .rewrite
+            .iter()
+            .filter_map(|rule| match CompiledRule::compile(rule) {
+                Ok(compiled) => Some(compiled),
+                Err(e) => {
+                    eprintln!("Invalid window rewrite rule: {}", e);
+                    None
+                }
+            })
+            .collect();
+
         Self {
             title: String::new(),
             class: String::new(),
+            address: String::new(),
+            workspace: String::new(),
+            floating: false,
+            fullscreen: false,
+            pid: 0,
+            options,
+            rules,
             config,
-            max_length: 50,
+            scroll_offset: 0,
+            last_step: Instant::now(),
+            monitor,
+            focused_monitor,
         }
     }
 
-    pub fn set_title(&mut self, title: String) {
-        if !title.is_empty() {
-            self.title = title;
-        } else {
-            self.title.clear();
+    pub fn set_focused_monitor(&mut self, monitor: String) {
+        self.focused_monitor = monitor;
+    }
+
+    fn monitor_focused(&self) -> bool {
+        match &self.monitor {
+            Some(monitor) => *monitor == self.focused_monitor,
+            None => true,
+        }
+    }
+
+    pub fn set_window(&mut self, window: &ActiveWindow) {
+        let switched = window.address != self.address;
+
+        self.class = window.class.clone();
+        self.set_title(window.title.clone());
+        self.address = window.address.clone();
+
+        // the event doesn't carry the rest, so ask for it straight away
+        if switched && let Err(e) = self.fetch_active_window() {
+            eprintln!("Active window fetch failed: {}", e);
         }
     }
 
+    fn set_title(&mut self, title: String) {
+        if title != self.title {
+            self.scroll_offset = 0;
+            self.last_step = Instant::now();
+        }
+        self.title = title;
+    }
+
     fn fetch_active_window(&mut self) -> Result<(), Box<dyn Error>> {
         let output = Command::new("hyprctl")
-            .args(&["activewindow", "-j"])
+            .args(["activewindow", "-j"])
             .output()?;
 
         if output.status.success() {
-            let window: ActiveWindow = serde_json::from_slice(&output.stdout)?;
-
-            if !window.title.is_empty() || !window.class.is_empty() {
-                self.title = window.title;
-                self.class = window.class;
-            } else {
-                self.title.clear();
-                self.class.clear();
-            }
+            // an empty object when nothing has focus
+            let window: ClientInfo = serde_json::from_slice(&output.stdout).unwrap_or_default();
+
+            self.class = window.class;
+            self.set_title(window.title);
+            self.address = window.address.trim_start_matches("0x").to_string();
+            self.workspace = window.workspace.map(|w| w.name).unwrap_or_default();
+            self.floating = window.floating;
+            self.fullscreen = window.fullscreen;
+            self.pid = window.pid;
         }
         Ok(())
     }
 
-    fn truncate_title(&self) -> String {
-        if self.title.len() > self.max_length {
-            format!("{}...", &self.title[..self.max_length - 3])
+    fn rewritten_title(&self) -> String {
+        self.rules
+            .iter()
+            .find_map(|rule| rule.apply(&self.class, &self.title))
+            .unwrap_or_else(|| self.title.clone())
+    }
+
+    fn get_icon(&self) -> &str {
+        self.options
+            .class_icons
+            .get(&self.class.to_lowercase())
+            .unwrap_or(&self.options.default_icon)
+    }
+
+    fn state_icon<'a>(&self, on: bool, icon: &'a str) -> &'a str {
+        if on { icon } else { "" }
+    }
+
+    fn should_scroll(&self, title: &str) -> bool {
+        self.options.marquee && title.width() > self.options.max_length
+    }
+
+    // as many steps as marquee_step fits into the time since the last one
+    fn advance_marquee(&mut self) {
+        let step = Duration::from_millis(self.options.marquee_step.max(1));
+        let steps = self.last_step.elapsed().as_millis() / step.as_millis();
+        if steps > 0 {
+            self.scroll_offset = self.scroll_offset.wrapping_add(steps as usize);
+            self.last_step += step * steps as u32;
+        }
+    }
+
+    fn display_title(&self) -> String {
+        let title = self.rewritten_title();
+
+        if self.should_scroll(&title) {
+            self.marquee_title(&title)
         } else {
-            self.title.clone()
+            truncate(
+                &title,
+                self.options.max_length,
+                &self.options.ellipsis,
+                self.options.ellipsis_position,
+            )
+        }
+    }
+
+    // a max_length wide window into the title looped with a gap
+    fn marquee_title(&self, title: &str) -> String {
+        let looped = format!("{}{}", title, self.options.marquee_gap);
+        let graphemes: Vec<&str> = looped.graphemes(true).collect();
+        let start = self.scroll_offset % graphemes.len();
+
+        let rotated = graphemes[start..]
+            .iter()
+            .chain(graphemes[..start].iter())
+            .copied()
+            .collect::<String>();
+        take_width(&rotated, self.options.max_length).to_string()
+    }
+}
+
+// Longest prefix of text that fits in width cells, split on grapheme boundaries
+fn take_width(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > width {
+            return &text[..i];
         }
     }
+    text
+}
+
+fn take_width_rev(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, grapheme) in text.grapheme_indices(true).rev() {
+        used += grapheme.width();
+        if used > width {
+            return &text[i + grapheme.len()..];
+        }
+    }
+    text
+}
+
+fn truncate(text: &str, max_width: usize, ellipsis: &str, position: EllipsisPosition) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+    // an ellipsis wider than the space left would overflow it by itself
+    let ellipsis = if ellipsis.width() > max_width {
+        ""
+    } else {
+        ellipsis
+    };
+
+    let budget = max_width.saturating_sub(ellipsis.width());
+    match position {
+        EllipsisPosition::Start => format!("{}{}", ellipsis, take_width_rev(text, budget)),
+        EllipsisPosition::Middle => {
+            let tail = budget / 2;
+            format!(
+                "{}{}{}",
+                take_width(text, budget - tail),
+                ellipsis,
+                take_width_rev(text, tail)
+            )
+        }
+        EllipsisPosition::End => format!("{}{}", take_width(text, budget), ellipsis),
+    }
 }
 
 impl Module for WindowModule {
@@ -75,27 +371,44 @@ impl Module for WindowModule {
 
     fn update(&mut self) -> Result<(), Box<dyn Error>> {
         self.fetch_active_window()?;
+
+        if self.should_scroll(&self.rewritten_title()) {
+            self.advance_marquee();
+        } else {
+            self.last_step = Instant::now();
+        }
         Ok(())
     }
 
-    fn render(&self) -> Span {
+    fn render(&self) -> Line<'_> {
+        if !self.monitor_focused() {
+            return Line::default();
+        }
+
         let format = self.config.format.as_deref().unwrap_or("{title}");
 
-        let display_title = self.truncate_title();
+        let display_title = self.display_title();
 
         if display_title.is_empty() {
-            return Span::raw("");
+            return Line::default();
         }
 
-        let text = format
-            .replace("{title}", &display_title)
-            .replace("{class}", &self.class);
-
-        if text.is_empty() {
-            Span::raw("")
-        } else {
-            Span::styled(text, Style::default().fg(crate::styles::TEXT))
-        }
+        markup::render(format, styles::window_style(), |text| {
+            text.replace("{title}", &display_title)
+                .replace("{class}", &self.class)
+                .replace("{icon}", self.get_icon())
+                .replace("{address}", &self.address)
+                .replace("{workspace}", &self.workspace)
+                .replace("{pid}", &self.pid.to_string())
+                .replace(
+                    "{floating}",
+                    self.state_icon(self.floating, &self.options.floating_icon),
+                )
+                .replace(
+                    "{fullscreen}",
+                    self.state_icon(self.fullscreen, &self.options.fullscreen_icon),
+                )
+        })
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -106,3 +419,62 @@ impl Module for WindowModule {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAMILY: &str = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+
+    #[test]
+    fn take_width_counts_cells_not_chars() {
+        assert_eq!(take_width("日本語テキスト", 5), "日本");
+        assert_eq!(take_width("日本語テキスト", 6), "日本語");
+        assert_eq!(take_width_rev("日本語テキスト", 5), "スト");
+    }
+
+    #[test]
+    fn take_width_keeps_combining_marks() {
+        let text = "e\u{301}e\u{301}e\u{301}";
+        assert_eq!(take_width(text, 2), "e\u{301}e\u{301}");
+        assert_eq!(take_width_rev(text, 1), "e\u{301}");
+    }
+
+    #[test]
+    fn take_width_never_splits_zwj_sequences() {
+        let text = format!("a{}b", FAMILY);
+        assert_eq!(take_width(&text, 2), "a");
+        assert_eq!(take_width(&text, 3), format!("a{}", FAMILY));
+        assert_eq!(take_width_rev(&text, 2), "b");
+        assert_eq!(take_width_rev(&text, 3), format!("{}b", FAMILY));
+    }
+
+    #[test]
+    fn truncate_fits_max_width() {
+        let title = "日本語のタイトル";
+        for position in [
+            EllipsisPosition::Start,
+            EllipsisPosition::Middle,
+            EllipsisPosition::End,
+        ] {
+            for max in 0..title.width() {
+                let truncated = truncate(title, max, "…", position);
+                assert!(truncated.width() <= max, "{:?} at {}", truncated, max);
+            }
+        }
+    }
+
+    #[test]
+    fn truncate_positions_the_ellipsis() {
+        assert_eq!(truncate("abcdef", 4, "…", EllipsisPosition::End), "abc…");
+        assert_eq!(truncate("abcdef", 4, "…", EllipsisPosition::Start), "…def");
+        assert_eq!(truncate("abcdef", 4, "…", EllipsisPosition::Middle), "ab…f");
+        assert_eq!(truncate("abc", 4, "…", EllipsisPosition::End), "abc");
+    }
+
+    #[test]
+    fn truncate_drops_an_ellipsis_that_does_not_fit() {
+        assert_eq!(truncate("abcdef", 0, "…", EllipsisPosition::End), "");
+        assert_eq!(truncate("abcdef", 2, "...", EllipsisPosition::End), "ab");
+    }
+}
@@ -1,34 +1,131 @@
-use super::{Module, ModuleConfig, ModulePosition};
+use super::{DEFAULT_CLASS_ICON, Module, ModuleConfig, ModulePosition, default_class_icons};
+use crate::hyprland::{HyprlandClient, WorkspaceRef};
+use crate::markup;
 use crate::styles;
 use ratatui::{
     style::Style,
     text::{Line, Span},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
 use std::process::Command;
-use std::{error::Error, process::Output};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 struct Workspace {
     id: i32,
     name: String,
     windows: i32,
+    #[serde(default)]
+    monitor: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Client {
+    address: String,
+    class: String,
+    workspace: WorkspaceRef,
+}
+
+impl Workspace {
+    fn placeholder(id: i32) -> Self {
+        Self {
+            id,
+            name: id.to_string(),
+            windows: 0,
+            monitor: String::new(),
+        }
+    }
+
+    fn is_special(&self) -> bool {
+        self.name == "special" || self.name.starts_with("special:")
+    }
+
+    // named workspaces get negative ids and a name that isn't the id
+    fn is_named(&self) -> bool {
+        !self.is_special() && self.name != self.id.to_string()
+    }
+
+    fn label(&self) -> &str {
+        self.name.strip_prefix("special:").unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum WorkspaceSort {
+    Id,
+    Name,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WorkspaceOptions {
+    // always listed, even before Hyprland creates them; only on an unbound
+    // bar, since they don't belong to any monitor yet
+    pub persistent: Vec<i32>,
+    // monitor name -> ids always listed on the bar bound to it
+    pub monitor_persistent: HashMap<String, Vec<i32>>,
+    pub sort: WorkspaceSort,
+    pub show_named: bool,
+    pub show_special: bool,
+    pub hide_empty: bool,
+    // keep other monitors' workspaces when the bar is bound to a monitor
+    pub show_other_monitors: bool,
+    // window class (lowercase) -> glyph, for {icons}
+    pub class_icons: HashMap<String, String>,
+    pub default_icon: String,
+    pub dedup_icons: bool,
+    // scrolling past the last workspace jumps back to the first
+    pub scroll_wrap: bool,
+    pub scroll_skip_empty: bool,
+}
+
+impl Default for WorkspaceOptions {
+    fn default() -> Self {
+        Self {
+            persistent: (1..=7).collect(),
+            monitor_persistent: HashMap::new(),
+            sort: WorkspaceSort::Id,
+            show_named: true,
+            show_special: false,
+            hide_empty: false,
+            show_other_monitors: false,
+            class_icons: default_class_icons(),
+            default_icon: DEFAULT_CLASS_ICON.to_string(),
+            dedup_icons: true,
+            scroll_wrap: false,
+            scroll_skip_empty: true,
+        }
+    }
 }
 
 pub struct WorkspaceModule {
-    active_id: u32,
+    active_id: i32,
+    active_special: Option<String>,
     workspaces: Vec<Workspace>,
+    clients: Vec<Client>,
+    // windows opened, closed or moved since clients were last fetched
+    clients_stale: bool,
+    urgent: HashSet<i32>,
     config: ModuleConfig,
-    max_workspaces: usize,
+    options: WorkspaceOptions,
+    monitor: Option<String>,
+    focused_monitor: String,
 }
 
 impl WorkspaceModule {
-    pub fn new(config: ModuleConfig) -> Self {
+    pub fn new(config: ModuleConfig, monitor: Option<String>) -> Self {
         let mut module = Self {
             active_id: 1,
+            active_special: None,
             workspaces: Vec::new(),
+            clients: Vec::new(),
+            clients_stale: true,
+            urgent: HashSet::new(),
+            options: config.options(),
             config,
-            max_workspaces: 7,
+            monitor,
+            focused_monitor: String::new(),
         };
         let _ = module.fetch_workspaces();
         let _ = module.fetch_active_workspace();
@@ -36,13 +133,73 @@ impl WorkspaceModule {
         module
     }
 
-    pub fn set_active_workspace(&mut self, id: u32) {
-        self.active_id = id.clamp(1, self.max_workspaces as u32);
+    pub fn set_active_workspace(&mut self, id: i32) {
+        self.clients_stale = true;
+        // workspace events always refer to the focused monitor
+        if !self.monitor_focused() {
+            return;
+        }
+        self.active_id = id;
+        self.urgent.remove(&id);
+    }
+
+    // clients are only fetched again after this, not on every tick
+    pub fn windows_changed(&mut self) {
+        self.clients_stale = true;
+    }
+
+    pub fn set_urgent(&mut self, address: &str) {
+        let address = address.trim_start_matches("0x");
+        // the window may have opened since the last fetch
+        if self.clients_stale && self.fetch_clients().is_ok() {
+            self.clients_stale = false;
+        }
+        if let Some(client) = self
+            .clients
+            .iter()
+            .find(|c| c.address.trim_start_matches("0x") == address)
+            && client.workspace.id != self.active_id
+        {
+            self.urgent.insert(client.workspace.id);
+        }
+    }
+
+    pub fn set_active_special(&mut self, name: String, monitor: String) {
+        if self.monitor.as_ref().is_some_and(|m| *m != monitor) {
+            return;
+        }
+        self.active_special = if name.is_empty() { None } else { Some(name) };
+    }
+
+    // focus moving between monitors changes the active workspace without a
+    // workspace event, so take it from the focused monitor's
+    pub fn set_focused_monitor(&mut self, monitor: String, workspace: &str) {
+        self.focused_monitor = monitor;
+        if !self.monitor_focused() {
+            return;
+        }
+        let id = self
+            .workspaces
+            .iter()
+            .find(|w| w.name == workspace)
+            .map(|w| w.id)
+            .or_else(|| workspace.parse().ok());
+        if let Some(id) = id {
+            self.active_id = id;
+            self.urgent.remove(&id);
+        }
+    }
+
+    fn monitor_focused(&self) -> bool {
+        match &self.monitor {
+            Some(monitor) => *monitor == self.focused_monitor,
+            None => true,
+        }
     }
 
     fn fetch_workspaces(&mut self) -> Result<(), Box<dyn Error>> {
         let output = Command::new("hyprctl")
-            .args(&["workspaces", "-j"])
+            .args(["workspaces", "-j"])
             .output()?;
 
         if output.status.success() {
@@ -51,24 +208,215 @@ impl WorkspaceModule {
         Ok(())
     }
 
-    fn fetch_active_workspace(&mut self) -> Result<(), Box<dyn Error>> {
-        let output = Command::new("hyprctl")
-            .args(&["activeworkspace", "-j"])
-            .output()?;
+    fn fetch_clients(&mut self) -> Result<(), Box<dyn Error>> {
+        let output = Command::new("hyprctl").args(["clients", "-j"]).output()?;
 
         if output.status.success() {
-            let workspace: Workspace = serde_json::from_slice(&output.stdout)?;
-            self.active_id = (workspace.id as u32).clamp(1, self.max_workspaces as u32);
+            self.clients = serde_json::from_slice(&output.stdout)?;
+        }
+        Ok(())
+    }
+
+    fn fetch_active_workspace(&mut self) -> Result<(), Box<dyn Error>> {
+        let monitors = HyprlandClient::new()?.get_monitors()?;
+        if let Some(m) = monitors.iter().find(|m| m.focused) {
+            self.focused_monitor = m.name.clone();
+        }
+
+        let own = self.own_monitor().to_string();
+        if let Some(m) = monitors.iter().find(|m| m.name == own) {
+            self.active_id = m.active_workspace.id;
         }
         Ok(())
     }
 
-    fn has_windows(&self, id: i32) -> bool {
-        self.workspaces
+    fn persistent_ids(&self) -> &[i32] {
+        match &self.monitor {
+            Some(monitor) => self
+                .options
+                .monitor_persistent
+                .get(monitor)
+                .map_or(&[], Vec::as_slice),
+            None => &self.options.persistent,
+        }
+    }
+
+    // an unbound bar belongs to whichever monitor has focus
+    fn own_monitor(&self) -> &str {
+        self.monitor.as_deref().unwrap_or(&self.focused_monitor)
+    }
+
+    fn on_other_monitor(&self, workspace: &Workspace) -> bool {
+        !workspace.monitor.is_empty() && workspace.monitor != self.own_monitor()
+    }
+
+    fn is_active(&self, workspace: &Workspace) -> bool {
+        if workspace.is_special() {
+            self.active_special.as_deref() == Some(workspace.name.as_str())
+        } else {
+            workspace.id == self.active_id
+        }
+    }
+
+    // the workspaces to draw, in display order
+    fn visible_workspaces(&self) -> Vec<Workspace> {
+        let mut list: Vec<Workspace> = self
+            .workspaces
+            .iter()
+            .filter(|w| {
+                self.monitor.is_none()
+                    || self.options.show_other_monitors
+                    || !self.on_other_monitor(w)
+            })
+            .filter(|w| self.options.show_special || !w.is_special())
+            .filter(|w| self.options.show_named || !w.is_named())
+            .filter(|w| {
+                !self.options.hide_empty
+                    || w.windows > 0
+                    || self.is_active(w)
+                    || self.persistent_ids().contains(&w.id)
+            })
+            .cloned()
+            .collect();
+
+        for &id in self.persistent_ids() {
+            // persistent ids that live on another monitor stay there
+            if !self.workspaces.iter().any(|w| w.id == id) {
+                list.push(Workspace::placeholder(id));
+            }
+        }
+
+        match self.options.sort {
+            // numbered first, then named, then special
+            WorkspaceSort::Id => list.sort_by(|a, b| {
+                let rank = |w: &Workspace| (w.is_special(), w.is_named());
+                rank(a).cmp(&rank(b)).then_with(|| {
+                    if a.is_special() || a.is_named() {
+                        a.label().cmp(b.label())
+                    } else {
+                        a.id.cmp(&b.id)
+                    }
+                })
+            }),
+            WorkspaceSort::Name => list.sort_by(|a, b| a.label().cmp(b.label())),
+        }
+
+        list
+    }
+
+    fn icons(&self, workspace: &Workspace) -> String {
+        let mut icons: Vec<&str> = Vec::new();
+
+        for client in self
+            .clients
             .iter()
-            .find(|w| w.id == id)
-            .map(|w| w.windows > 0)
-            .unwrap_or(false)
+            .filter(|c| c.workspace.id == workspace.id)
+        {
+            let icon = self
+                .options
+                .class_icons
+                .get(&client.class.to_lowercase())
+                .unwrap_or(&self.options.default_icon);
+
+            if icon.is_empty() || (self.options.dedup_icons && icons.contains(&icon.as_str())) {
+                continue;
+            }
+            icons.push(icon);
+        }
+
+        icons.join(" ")
+    }
+
+    fn style_for(&self, workspace: &Workspace) -> Style {
+        if self.is_active(workspace) {
+            styles::workspace_active_style()
+        } else if self.urgent.contains(&workspace.id) {
+            styles::workspace_urgent_style()
+        } else if self.on_other_monitor(workspace) {
+            styles::workspace_other_monitor_style()
+        } else if workspace.windows > 0 {
+            styles::workspace_style()
+        } else {
+            styles::workspace_empty_style()
+        }
+    }
+
+    // The workspace's label, padded by a space each side inside its style
+    fn workspace_spans(&self, workspace: &Workspace) -> Vec<Span<'static>> {
+        let format = self.config.format.as_deref().unwrap_or("{name}");
+        let style = self.style_for(workspace);
+
+        let mut spans = markup::render(format, style, |text| {
+            text.replace("{id}", &workspace.id.to_string())
+                .replace("{name}", workspace.label())
+                .replace("{windows}", &workspace.windows.to_string())
+                .replace("{icons}", &self.icons(workspace))
+        })
+        .spans;
+
+        if spans.is_empty() {
+            spans.push(Span::styled(" ", style));
+        }
+        if let Some(first) = spans.first_mut() {
+            first.content = format!(" {}", first.content.trim_start()).into();
+        }
+        if let Some(last) = spans.last_mut() {
+            last.content = format!("{} ", last.content.trim_end()).into();
+        }
+        spans
+    }
+
+    // the workspace whose label covers column x of the module
+    fn workspace_at(&self, x: u16) -> Option<Workspace> {
+        let mut start = 0;
+
+        for workspace in self.visible_workspaces() {
+            let width: usize = self
+                .workspace_spans(&workspace)
+                .iter()
+                .map(|span| span.width())
+                .sum();
+            let end = start + width as u16;
+            if x >= start && x < end {
+                return Some(workspace);
+            }
+            start = end;
+        }
+        None
+    }
+
+    fn scroll_target(&self, step: i32) -> Option<Workspace> {
+        let list: Vec<Workspace> = self
+            .visible_workspaces()
+            .into_iter()
+            .filter(|w| !w.is_special())
+            .filter(|w| !self.options.scroll_skip_empty || w.windows > 0 || self.is_active(w))
+            .collect();
+
+        let current = list.iter().position(|w| self.is_active(w))? as i32;
+        let len = list.len() as i32;
+        let next = if self.options.scroll_wrap {
+            (current + step).rem_euclid(len)
+        } else {
+            current + step
+        };
+
+        list.get(usize::try_from(next).ok()?).cloned()
+    }
+
+    fn switch_to(&self, workspace: &Workspace) -> Result<(), Box<dyn Error>> {
+        let (dispatcher, target) = if workspace.is_special() {
+            ("togglespecialworkspace", workspace.label().to_string())
+        } else if workspace.is_named() {
+            ("workspace", format!("name:{}", workspace.name))
+        } else {
+            ("workspace", workspace.id.to_string())
+        };
+
+        Command::new("hyprctl")
+            .args(["dispatch", dispatcher, &target])
+            .spawn()?;
+        Ok(())
     }
 }
 
@@ -83,39 +431,43 @@ impl Module for WorkspaceModule {
 
     fn update(&mut self) -> Result<(), Box<dyn Error>> {
         self.fetch_workspaces()?;
+        if self.clients_stale {
+            self.fetch_clients()?;
+            self.clients_stale = false;
+        }
         Ok(())
     }
 
-    fn render(&self) -> Span {
-        let mut text = String::from(" ");
-
-        for i in 1..=self.max_workspaces {
-            let id = i as i32;
-
-            let is_active = id == self.active_id as i32
-                && self.active_id >= 1
-                && self.active_id <= self.max_workspaces as u32;
-
-            if id == self.active_id as i32 {
-                text.push_str(&format!("[~{}~]", i));
-            } else if self.has_windows(id) {
-                text.push_str(&format!("[({})]", i));
-            } else {
-                text.push_str(&format!("[ {} ]", i));
-            }
-        }
-
-        Span::styled(text, styles::workspace_style())
+    fn render(&self) -> Line<'_> {
+        Line::from(
+            self.visible_workspaces()
+                .iter()
+                .flat_map(|workspace| self.workspace_spans(workspace))
+                .collect::<Vec<_>>(),
+        )
     }
 
     fn on_click(&mut self, x: u16, _y: u16) -> Result<(), Box<dyn Error>> {
-        let workspace_width = 5;
-        let clicked_workspace = ((x / workspace_width) + 1).min(10);
+        if let Some(workspace) = self.workspace_at(x) {
+            self.switch_to(&workspace)?;
+        }
+        Ok(())
+    }
 
-        Command::new("hyprctl")
-            .args(&["dispatch", "workspace", &clicked_workspace.to_string()])
-            .spawn()?;
+    fn on_scroll(&mut self, delta: i32) -> Result<(), Box<dyn Error>> {
+        let step = delta.signum();
 
+        match self.scroll_target(step) {
+            Some(workspace) => self.switch_to(&workspace)?,
+            // the active workspace isn't listed, let Hyprland pick the neighbour
+            None if !self.visible_workspaces().iter().any(|w| self.is_active(w)) => {
+                let target = if step > 0 { "e+1" } else { "e-1" };
+                Command::new("hyprctl")
+                    .args(["dispatch", "workspace", target])
+                    .spawn()?;
+            }
+            None => {}
+        }
         Ok(())
     }
 
@@ -127,3 +479,55 @@ impl Module for WorkspaceModule {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::config_with;
+
+    fn workspace(id: i32, name: &str, monitor: &str) -> Workspace {
+        Workspace {
+            id,
+            name: name.to_string(),
+            windows: 1,
+            monitor: monitor.to_string(),
+        }
+    }
+
+    fn module(monitor: Option<&str>) -> WorkspaceModule {
+        let mut module = WorkspaceModule::new(
+            config_with(serde_json::json!({})),
+            monitor.map(String::from),
+        );
+        module.workspaces = vec![
+            workspace(1, "1", "DP-1"),
+            workspace(2, "2", "DP-2"),
+            workspace(-1337, "web", "DP-2"),
+        ];
+        module.active_id = 1;
+        module.focused_monitor = "DP-1".to_string();
+        module
+    }
+
+    #[test]
+    fn unbound_bar_follows_focus_across_monitors() {
+        let mut module = module(None);
+
+        module.set_focused_monitor("DP-2".to_string(), "2");
+        assert_eq!(module.active_id, 2);
+
+        module.set_focused_monitor("DP-2".to_string(), "web");
+        assert_eq!(module.active_id, -1337);
+    }
+
+    #[test]
+    fn bound_bar_keeps_its_own_workspace() {
+        let mut module = module(Some("DP-1"));
+
+        module.set_focused_monitor("DP-2".to_string(), "2");
+        assert_eq!(module.active_id, 1);
+
+        module.set_focused_monitor("DP-1".to_string(), "1");
+        assert_eq!(module.active_id, 1);
+    }
+}
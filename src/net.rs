@@ -0,0 +1,567 @@
+// Wi-Fi and address details for the network module, asked of the kernel
+// directly (nl80211 over generic netlink, getifaddrs) instead of ip/iwgetid
+use std::ffi::CStr;
+use std::fs;
+use std::io;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::ptr;
+
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_CMD_GET_STATION: u8 = 17;
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_STA_INFO: u16 = 21;
+const NL80211_ATTR_WIPHY_FREQ: u16 = 38;
+const NL80211_ATTR_SSID: u16 = 52;
+const NL80211_STA_INFO_SIGNAL: u16 = 7;
+
+const NLMSG_HDR_LEN: usize = 16;
+const GENL_HDR_LEN: usize = 4;
+// the top two bits of nla_type are the nested / byte-order flags
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+#[derive(Debug, Clone, Default)]
+pub struct WifiInfo {
+    pub ssid: String,
+    // MHz
+    pub frequency: Option<u32>,
+    // dBm
+    pub signal: Option<i32>,
+}
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn read_u16(bytes: &[u8]) -> Option<u16> {
+    Some(u16::from_ne_bytes(bytes.get(..2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8]) -> Option<u32> {
+    Some(u32::from_ne_bytes(bytes.get(..4)?.try_into().ok()?))
+}
+
+// (type, payload) of each netlink attribute in data
+fn attributes(data: &[u8]) -> Vec<(u16, &[u8])> {
+    let mut attrs = Vec::new();
+    let mut rest = data;
+
+    while rest.len() >= 4 {
+        let len = read_u16(rest).unwrap_or(0) as usize;
+        let kind = read_u16(&rest[2..]).unwrap_or(0) & NLA_TYPE_MASK;
+        if len < 4 || len > rest.len() {
+            break;
+        }
+        attrs.push((kind, &rest[4..len]));
+        rest = &rest[align(len).min(rest.len())..];
+    }
+    attrs
+}
+
+fn find_attribute(data: &[u8], kind: u16) -> Option<&[u8]> {
+    attributes(data)
+        .into_iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, payload)| payload)
+}
+
+fn encode_request(family: u16, cmd: u8, flags: u16, seq: u32, attrs: &[(u16, &[u8])]) -> Vec<u8> {
+    let mut msg = vec![0u8; NLMSG_HDR_LEN + GENL_HDR_LEN];
+    for (kind, payload) in attrs {
+        msg.extend_from_slice(&((4 + payload.len()) as u16).to_ne_bytes());
+        msg.extend_from_slice(&kind.to_ne_bytes());
+        msg.extend_from_slice(payload);
+        msg.resize(align(msg.len()), 0);
+    }
+    let len = msg.len() as u32;
+    msg[0..4].copy_from_slice(&len.to_ne_bytes());
+    msg[4..6].copy_from_slice(&family.to_ne_bytes());
+    msg[6..8].copy_from_slice(&(libc::NLM_F_REQUEST as u16 | flags).to_ne_bytes());
+    msg[8..12].copy_from_slice(&seq.to_ne_bytes());
+    msg[NLMSG_HDR_LEN] = cmd;
+    msg[NLMSG_HDR_LEN + 1] = 1;
+    msg
+}
+
+// Adds the attribute block of every reply to seq in one recv's worth of
+// messages to replies; true once nothing more is coming
+fn read_replies(mut data: &[u8], seq: u32, replies: &mut Vec<Vec<u8>>) -> io::Result<bool> {
+    while data.len() >= NLMSG_HDR_LEN {
+        let len = read_u32(data).unwrap_or(0) as usize;
+        let kind = read_u16(&data[4..]).unwrap_or(0);
+        let msg_flags = read_u16(&data[6..]).unwrap_or(0);
+        let msg_seq = read_u32(&data[8..]).unwrap_or(0);
+        if len < NLMSG_HDR_LEN || len > data.len() {
+            return Err(io::Error::from(io::ErrorKind::InvalidData));
+        }
+
+        if msg_seq == seq {
+            match kind as i32 {
+                libc::NLMSG_DONE => return Ok(true),
+                libc::NLMSG_ERROR => {
+                    // zero is a plain ack, otherwise a negated errno
+                    return match read_u32(&data[NLMSG_HDR_LEN..len]) {
+                        Some(0) => Ok(true),
+                        Some(code) => Err(io::Error::from_raw_os_error(-(code as i32))),
+                        None => Err(io::Error::from(io::ErrorKind::InvalidData)),
+                    };
+                }
+                _ => {
+                    if len >= NLMSG_HDR_LEN + GENL_HDR_LEN {
+                        replies.push(data[NLMSG_HDR_LEN + GENL_HDR_LEN..len].to_vec());
+                    }
+                    if msg_flags & libc::NLM_F_MULTI as u16 == 0 {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+        data = &data[align(len).min(data.len())..];
+    }
+    Ok(false)
+}
+
+struct GenlSocket {
+    fd: OwnedFd,
+    seq: u32,
+}
+
+impl GenlSocket {
+    fn open() -> io::Result<Self> {
+        let raw = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_GENERIC,
+            )
+        };
+        if raw < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+        // a reply that never comes shouldn't stall the bar
+        let timeout = libc::timeval {
+            tv_sec: 1,
+            tv_usec: 0,
+        };
+        let ret = unsafe {
+            libc::setsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &timeout as *const libc::timeval as *const libc::c_void,
+                mem::size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { fd, seq: 0 })
+    }
+
+    // Sends one generic netlink request and returns the attribute block of
+    // every reply message
+    fn request(
+        &mut self,
+        family: u16,
+        cmd: u8,
+        flags: u16,
+        attrs: &[(u16, &[u8])],
+    ) -> io::Result<Vec<Vec<u8>>> {
+        self.seq = self.seq.wrapping_add(1);
+        let msg = encode_request(family, cmd, flags, self.seq, attrs);
+
+        let sent = unsafe {
+            libc::send(
+                self.fd.as_raw_fd(),
+                msg.as_ptr() as *const libc::c_void,
+                msg.len(),
+                0,
+            )
+        };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut replies = Vec::new();
+        let mut buf = vec![0u8; 32 * 1024];
+        loop {
+            let received = unsafe {
+                libc::recv(
+                    self.fd.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    0,
+                )
+            };
+            if received < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            if read_replies(&buf[..received as usize], self.seq, &mut replies)? {
+                return Ok(replies);
+            }
+        }
+    }
+
+    fn family_id(&mut self, name: &str) -> io::Result<u16> {
+        let mut name = name.as_bytes().to_vec();
+        name.push(0);
+
+        let replies = self.request(
+            GENL_ID_CTRL,
+            CTRL_CMD_GETFAMILY,
+            0,
+            &[(CTRL_ATTR_FAMILY_NAME, &name)],
+        )?;
+        replies
+            .iter()
+            .find_map(|reply| find_attribute(reply, CTRL_ATTR_FAMILY_ID).and_then(read_u16))
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+}
+
+// An nl80211 socket with its family id looked up once, kept by the network
+// module between updates
+pub struct Nl80211 {
+    socket: GenlSocket,
+    family: u16,
+}
+
+impl Nl80211 {
+    pub fn open() -> io::Result<Self> {
+        let mut socket = GenlSocket::open()?;
+        let family = socket.family_id("nl80211")?;
+        Ok(Self { socket, family })
+    }
+
+    fn info(&mut self, ifindex: u32) -> io::Result<WifiInfo> {
+        let ifindex = ifindex.to_ne_bytes();
+
+        let interfaces = self.socket.request(
+            self.family,
+            NL80211_CMD_GET_INTERFACE,
+            0,
+            &[(NL80211_ATTR_IFINDEX, &ifindex)],
+        )?;
+        let Some(interface) = interfaces.first() else {
+            return Ok(WifiInfo::default());
+        };
+
+        // on a managed interface the only station is the access point
+        let stations = self.socket.request(
+            self.family,
+            NL80211_CMD_GET_STATION,
+            libc::NLM_F_DUMP as u16,
+            &[(NL80211_ATTR_IFINDEX, &ifindex)],
+        )?;
+        let signal = stations.iter().find_map(|station| {
+            let info = find_attribute(station, NL80211_ATTR_STA_INFO)?;
+            let signal = find_attribute(info, NL80211_STA_INFO_SIGNAL)?;
+            signal.first().map(|&dbm| dbm as i8 as i32)
+        });
+
+        Ok(WifiInfo {
+            // absent while not associated
+            ssid: find_attribute(interface, NL80211_ATTR_SSID)
+                .map(|ssid| String::from_utf8_lossy(ssid).to_string())
+                .unwrap_or_default(),
+            frequency: find_attribute(interface, NL80211_ATTR_WIPHY_FREQ).and_then(read_u32),
+            signal,
+        })
+    }
+}
+
+fn nl80211_info(nl80211: &mut Option<Nl80211>, ifindex: u32) -> io::Result<WifiInfo> {
+    let nl80211 = match nl80211 {
+        Some(nl80211) => nl80211,
+        None => nl80211.insert(Nl80211::open()?),
+    };
+    nl80211.info(ifindex)
+}
+
+// signal level column of /proc/net/wireless, for kernels without nl80211
+fn proc_wireless_signal(interface: &str) -> Option<i32> {
+    let wireless = fs::read_to_string("/proc/net/wireless").ok()?;
+    wireless_signal(&wireless, interface)
+}
+
+fn wireless_signal(wireless: &str, interface: &str) -> Option<i32> {
+    wireless.lines().skip(2).find_map(|line| {
+        let (name, stats) = line.split_once(':')?;
+        if name.trim() != interface {
+            return None;
+        }
+        let level = stats.split_whitespace().nth(2)?;
+        level
+            .trim_end_matches('.')
+            .parse::<f64>()
+            .ok()
+            .map(|l| l as i32)
+    })
+}
+
+// nl80211 is opened on first use, and again after a query fails
+pub fn wifi_info(nl80211: &mut Option<Nl80211>, interface: &str) -> WifiInfo {
+    let ifindex = fs::read_to_string(format!("/sys/class/net/{}/ifindex", interface))
+        .ok()
+        .and_then(|s| s.trim().parse::<u32>().ok());
+
+    let mut info = match ifindex.map(|ifindex| nl80211_info(nl80211, ifindex)) {
+        Some(Ok(info)) => info,
+        Some(Err(e)) => {
+            eprintln!("nl80211 query failed: {}", e);
+            *nl80211 = None;
+            WifiInfo::default()
+        }
+        None => WifiInfo::default(),
+    };
+    if info.signal.is_none() {
+        info.signal = proc_wireless_signal(interface);
+    }
+    info
+}
+
+pub fn is_link_local(addr: &Ipv6Addr) -> bool {
+    addr.segments()[0] & 0xffc0 == 0xfe80
+}
+
+// First IPv4 address and first IPv6 address (global ones before link-local)
+pub fn addresses(interface: &str) -> (Option<Ipv4Addr>, Option<Ipv6Addr>) {
+    let mut ifap: *mut libc::ifaddrs = ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut ifap) } != 0 {
+        return (None, None);
+    }
+
+    let mut ipv4 = None;
+    let mut ipv6: Option<Ipv6Addr> = None;
+    let mut cursor = ifap;
+    while !cursor.is_null() {
+        let ifa = unsafe { &*cursor };
+        cursor = ifa.ifa_next;
+
+        if ifa.ifa_addr.is_null() {
+            continue;
+        }
+        let name = unsafe { CStr::from_ptr(ifa.ifa_name) };
+        if name.to_bytes() != interface.as_bytes() {
+            continue;
+        }
+
+        match unsafe { (*ifa.ifa_addr).sa_family } as i32 {
+            libc::AF_INET if ipv4.is_none() => {
+                let addr = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in) };
+                ipv4 = Some(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)));
+            }
+            libc::AF_INET6 => {
+                let addr = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in6) };
+                let addr = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+                if ipv6.is_none_or(|current| is_link_local(&current) && !is_link_local(&addr)) {
+                    ipv6 = Some(addr);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    unsafe { libc::freeifaddrs(ifap) };
+    (ipv4, ipv6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute(kind: u16, payload: &[u8]) -> Vec<u8> {
+        let mut attr = ((4 + payload.len()) as u16).to_ne_bytes().to_vec();
+        attr.extend_from_slice(&kind.to_ne_bytes());
+        attr.extend_from_slice(payload);
+        attr.resize(align(attr.len()), 0);
+        attr
+    }
+
+    fn message(kind: u16, flags: u16, seq: u32, payload: &[u8]) -> Vec<u8> {
+        let len = (NLMSG_HDR_LEN + payload.len()) as u32;
+        let mut msg = len.to_ne_bytes().to_vec();
+        msg.extend_from_slice(&kind.to_ne_bytes());
+        msg.extend_from_slice(&flags.to_ne_bytes());
+        msg.extend_from_slice(&seq.to_ne_bytes());
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        msg.extend_from_slice(payload);
+        msg.resize(align(msg.len()), 0);
+        msg
+    }
+
+    // a generic netlink reply carrying attrs
+    fn reply(flags: u16, seq: u32, attrs: &[u8]) -> Vec<u8> {
+        let mut payload = vec![NL80211_CMD_GET_INTERFACE, 1, 0, 0];
+        payload.extend_from_slice(attrs);
+        message(0x1c, flags, seq, &payload)
+    }
+
+    fn error(seq: u32, code: i32) -> Vec<u8> {
+        message(libc::NLMSG_ERROR as u16, 0, seq, &code.to_ne_bytes())
+    }
+
+    #[test]
+    fn attributes_are_split_and_padded() {
+        let mut data = attribute(NL80211_ATTR_SSID, b"home");
+        data.extend(attribute(NL80211_ATTR_WIPHY_FREQ, &5180u32.to_ne_bytes()));
+        // 5 byte payload, padded to 8
+        data.extend(attribute(CTRL_ATTR_FAMILY_NAME, b"abcde"));
+
+        let attrs = attributes(&data);
+        assert_eq!(attrs.len(), 3);
+        assert_eq!(attrs[0], (NL80211_ATTR_SSID, &b"home"[..]));
+        assert_eq!(read_u32(attrs[1].1), Some(5180));
+        assert_eq!(attrs[2], (CTRL_ATTR_FAMILY_NAME, &b"abcde"[..]));
+    }
+
+    #[test]
+    fn nested_flag_is_masked() {
+        let inner = attribute(NL80211_STA_INFO_SIGNAL, &[-61i8 as u8]);
+        let data = attribute(NL80211_ATTR_STA_INFO | 0x8000, &inner);
+
+        let info = find_attribute(&data, NL80211_ATTR_STA_INFO).unwrap();
+        let signal = find_attribute(info, NL80211_STA_INFO_SIGNAL).unwrap();
+        assert_eq!(signal[0] as i8, -61);
+    }
+
+    #[test]
+    fn truncated_attributes_stop_parsing() {
+        let mut data = attribute(NL80211_ATTR_SSID, b"home");
+        // claims 12 bytes, only 6 follow
+        data.extend_from_slice(&12u16.to_ne_bytes());
+        data.extend_from_slice(&NL80211_ATTR_IFINDEX.to_ne_bytes());
+        data.extend_from_slice(&[1, 2]);
+        assert_eq!(attributes(&data), [(NL80211_ATTR_SSID, &b"home"[..])]);
+
+        // a length shorter than the header itself
+        let mut data = 2u16.to_ne_bytes().to_vec();
+        data.extend_from_slice(&NL80211_ATTR_SSID.to_ne_bytes());
+        assert!(attributes(&data).is_empty());
+
+        // fewer bytes than a header
+        assert!(attributes(&[8, 0, 3]).is_empty());
+    }
+
+    #[test]
+    fn last_attribute_may_skip_its_padding() {
+        let mut data = attribute(NL80211_ATTR_IFINDEX, &3u32.to_ne_bytes());
+        data.extend_from_slice(&7u16.to_ne_bytes());
+        data.extend_from_slice(&NL80211_ATTR_SSID.to_ne_bytes());
+        data.extend_from_slice(b"abc");
+
+        let attrs = attributes(&data);
+        assert_eq!(attrs.len(), 2);
+        assert_eq!(attrs[1], (NL80211_ATTR_SSID, &b"abc"[..]));
+    }
+
+    #[test]
+    fn request_is_framed() {
+        let ifindex = 3u32.to_ne_bytes();
+        let msg = encode_request(
+            0x1c,
+            NL80211_CMD_GET_STATION,
+            libc::NLM_F_DUMP as u16,
+            7,
+            &[(NL80211_ATTR_IFINDEX, &ifindex)],
+        );
+
+        assert_eq!(msg.len(), NLMSG_HDR_LEN + GENL_HDR_LEN + 8);
+        assert_eq!(read_u32(&msg), Some(msg.len() as u32));
+        assert_eq!(read_u16(&msg[4..]), Some(0x1c));
+        assert_eq!(
+            read_u16(&msg[6..]),
+            Some((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16)
+        );
+        assert_eq!(read_u32(&msg[8..]), Some(7));
+        assert_eq!(msg[NLMSG_HDR_LEN], NL80211_CMD_GET_STATION);
+        assert_eq!(
+            attributes(&msg[NLMSG_HDR_LEN + GENL_HDR_LEN..]),
+            [(NL80211_ATTR_IFINDEX, &ifindex[..])]
+        );
+    }
+
+    #[test]
+    fn ack_ends_the_reply() {
+        let mut replies = Vec::new();
+        assert!(read_replies(&error(4, 0), 4, &mut replies).unwrap());
+        assert!(replies.is_empty());
+    }
+
+    #[test]
+    fn error_carries_the_errno() {
+        let mut replies = Vec::new();
+        let err = read_replies(&error(4, -libc::ENOENT), 4, &mut replies).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+    }
+
+    #[test]
+    fn single_reply() {
+        let mut replies = Vec::new();
+        let data = reply(0, 4, &attribute(NL80211_ATTR_SSID, b"home"));
+
+        assert!(read_replies(&data, 4, &mut replies).unwrap());
+        assert_eq!(replies.len(), 1);
+        assert_eq!(
+            find_attribute(&replies[0], NL80211_ATTR_SSID),
+            Some(&b"home"[..])
+        );
+    }
+
+    #[test]
+    fn multipart_until_done() {
+        let multi = libc::NLM_F_MULTI as u16;
+        let mut replies = Vec::new();
+
+        // a stale reply to an earlier request is skipped
+        let mut first = reply(multi, 3, &attribute(NL80211_ATTR_SSID, b"old"));
+        first.extend(reply(multi, 4, &attribute(NL80211_ATTR_SSID, b"a")));
+        first.extend(reply(multi, 4, &attribute(NL80211_ATTR_SSID, b"b")));
+        assert!(!read_replies(&first, 4, &mut replies).unwrap());
+        assert_eq!(replies.len(), 2);
+
+        let mut second = reply(multi, 4, &attribute(NL80211_ATTR_SSID, b"c"));
+        second.extend(message(
+            libc::NLMSG_DONE as u16,
+            multi,
+            4,
+            &0i32.to_ne_bytes(),
+        ));
+        assert!(read_replies(&second, 4, &mut replies).unwrap());
+        assert_eq!(replies.len(), 3);
+    }
+
+    #[test]
+    fn truncated_message_is_invalid() {
+        let mut data = reply(0, 4, &attribute(NL80211_ATTR_SSID, b"home"));
+        data.truncate(data.len() - 4);
+
+        let err = read_replies(&data, 4, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    const WIRELESS: &str = "\
+Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+wlp2s0: 0000   54.  -56.  -256        0      0      0      0     29        0
+ wlan1: 0000   30.  -80.  -256        0      0      0      0      0        0
+";
+
+    #[test]
+    fn proc_wireless_level() {
+        assert_eq!(wireless_signal(WIRELESS, "wlp2s0"), Some(-56));
+        assert_eq!(wireless_signal(WIRELESS, "wlan1"), Some(-80));
+        assert_eq!(wireless_signal(WIRELESS, "eth0"), None);
+        assert_eq!(wireless_signal("", "wlp2s0"), None);
+    }
+}
@@ -0,0 +1,48 @@
+// Desktop notifications, behind a trait so modules don't care how they are
+// delivered
+use std::error::Error;
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Urgency::Normal => "normal",
+            Urgency::Critical => "critical",
+        }
+    }
+}
+
+pub trait Notifier: Send {
+    fn notify(&self, summary: &str, body: &str, urgency: Urgency) -> Result<(), Box<dyn Error>>;
+}
+
+// libnotify's notify-send; most daemons keep critical ones up until dismissed
+pub struct NotifySend {
+    pub timeout_ms: u32,
+}
+
+impl Default for NotifySend {
+    fn default() -> Self {
+        Self { timeout_ms: 5000 }
+    }
+}
+
+impl Notifier for NotifySend {
+    fn notify(&self, summary: &str, body: &str, urgency: Urgency) -> Result<(), Box<dyn Error>> {
+        Command::new("notify-send")
+            .arg("-u")
+            .arg(urgency.as_str())
+            .arg("-t")
+            .arg(self.timeout_ms.to_string())
+            .arg(summary)
+            .arg(body)
+            .spawn()?;
+        Ok(())
+    }
+}
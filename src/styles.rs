@@ -13,6 +13,10 @@ pub fn workspace_style() -> Style {
     Style::default().fg(TEXT)
 }
 
+pub fn workspace_empty_style() -> Style {
+    Style::default().fg(TEXT).add_modifier(Modifier::DIM)
+}
+
 pub fn workspace_active_style() -> Style {
     Style::default()
         .fg(SURFACE)
@@ -20,20 +24,106 @@ pub fn workspace_active_style() -> Style {
         .add_modifier(Modifier::BOLD)
 }
 
+pub fn workspace_urgent_style() -> Style {
+    Style::default()
+        .fg(SURFACE)
+        .bg(RED)
+        .add_modifier(Modifier::BOLD)
+}
+
+pub fn workspace_other_monitor_style() -> Style {
+    Style::default()
+        .fg(PURPLE)
+        .add_modifier(Modifier::UNDERLINED)
+}
+
+pub fn monitor_style(focused: bool) -> Style {
+    if focused {
+        Style::default().fg(PRIMARY).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(TEXT)
+    }
+}
+
+pub fn window_style() -> Style {
+    Style::default().fg(TEXT)
+}
+
 pub fn clock_style() -> Style {
     Style::default().fg(PRIMARY).add_modifier(Modifier::BOLD)
 }
 
+pub fn tooltip_style() -> Style {
+    Style::default().fg(TEXT).bg(SURFACE)
+}
+
+pub fn tooltip_border_style() -> Style {
+    Style::default().fg(PRIMARY)
+}
+
+pub fn tooltip_label_style() -> Style {
+    Style::default().fg(PURPLE).add_modifier(Modifier::DIM)
+}
+
+pub fn tooltip_value_style() -> Style {
+    Style::default().fg(TEXT)
+}
+
+pub fn calendar_header_style() -> Style {
+    Style::default().fg(TEXT).add_modifier(Modifier::DIM)
+}
+
+pub fn calendar_week_style() -> Style {
+    Style::default().fg(PURPLE)
+}
+
+pub fn calendar_today_style() -> Style {
+    Style::default()
+        .fg(SURFACE)
+        .bg(PRIMARY)
+        .add_modifier(Modifier::BOLD)
+}
+
 pub fn cpu_style() -> Style {
     Style::default().fg(PURPLE)
 }
 
+pub fn temperature_style(critical: bool) -> Style {
+    if critical {
+        Style::default().fg(RED).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(YELLOW)
+    }
+}
+
+pub fn custom_style() -> Style {
+    Style::default().fg(TEXT)
+}
+
 pub fn memory_style() -> Style {
     Style::default().fg(PINK)
 }
 
-pub fn network_style() -> Style {
-    Style::default().fg(PURPLE)
+pub fn disk_style(percentage: f64, warning: f64, critical: f64) -> Style {
+    if percentage >= critical {
+        Style::default().fg(RED).add_modifier(Modifier::BOLD)
+    } else if percentage >= warning {
+        Style::default().fg(YELLOW)
+    } else {
+        Style::default().fg(GREEN)
+    }
+}
+
+pub fn disk_io_style() -> Style {
+    Style::default().fg(PINK)
+}
+
+pub fn network_style(online: bool) -> Style {
+    if online {
+        Style::default().fg(PURPLE)
+    } else {
+        Style::default().fg(RED)
+    }
 }
 
 pub fn battery_style(charging: bool, level: u8) -> Style {
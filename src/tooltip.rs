@@ -0,0 +1,83 @@
+// A module's tooltip in a terminal window of its own (`--tooltip <module>`),
+// for when the bar has no spare rows to draw it under itself
+use crossterm::{
+    event::{self, Event, KeyCode},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    style::Style,
+    widgets::{Block, Borders, Paragraph},
+};
+use std::error::Error;
+use std::io;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::module_manager::create_module;
+use crate::modules::Module;
+use crate::modules::battery::BatteryModule;
+use crate::styles::*;
+
+// float it with a windowrule on the jlessbar-tooltip class
+pub fn open_window(module: &str) -> Result<(), Box<dyn Error>> {
+    Command::new("kitty")
+        .args(["--class", "jlessbar-tooltip", "-e"])
+        .arg(std::env::current_exe()?)
+        .args(["--tooltip", module])
+        .spawn()?;
+    Ok(())
+}
+
+// The module as the bar would build it, minus anything that acts on its
+// own: the bar's copy is still running and has already done that
+fn tooltip_module(name: &str, config: &Config) -> Option<Box<dyn Module>> {
+    let mut module = create_module(name, config)?;
+    if let Some(battery) = module.as_any_mut().downcast_mut::<BatteryModule>() {
+        battery.disable_alerts();
+    }
+    Some(module)
+}
+
+pub fn run(config: &Config, name: &str) -> Result<(), Box<dyn Error>> {
+    let mut module = tooltip_module(name, config).ok_or("unknown module")?;
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // refreshed every second until q or Esc
+    loop {
+        if let Err(e) = module.update() {
+            eprintln!("Module update failed: {}", e);
+        }
+
+        terminal.draw(|f| {
+            let text = module.tooltip().unwrap_or_default();
+            let paragraph = Paragraph::new(text).style(tooltip_style()).block(
+                Block::default()
+                    .title(format!(" {} ", module.name()))
+                    .borders(Borders::ALL)
+                    .border_style(tooltip_border_style())
+                    .style(Style::default().bg(SURFACE)),
+            );
+            f.render_widget(paragraph, f.size());
+        })?;
+
+        if event::poll(Duration::from_secs(1))?
+            && let Event::Key(key) = event::read()?
+            && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+        {
+            break;
+        }
+    }
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    Ok(())
+}
@@ -1,18 +1,31 @@
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Modifier, Style, Styled},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph},
 };
 
 use crate::app::App;
-use crate::module_manager::ModuleManager;
+use crate::config::FullscreenMode;
+use crate::module_manager::{ModuleManager, ModuleRegion};
 use crate::modules::ModulePosition;
 use crate::styles::*;
 
-pub fn render_ui(f: &mut Frame, app: &App, module_manager: &ModuleManager) {
+// Draws the bar and returns where each module ended up, for mouse hit-testing
+pub fn render_ui(f: &mut Frame, app: &App, module_manager: &ModuleManager) -> Vec<ModuleRegion> {
     let size = f.size();
+    let mut regions = Vec::new();
+
+    match app.fullscreen_mode() {
+        // leave the buffer empty so the bar draws nothing
+        Some(FullscreenMode::Hide) => return regions,
+        Some(FullscreenMode::Minimal) => {
+            render_minimal(f, app, module_manager, &mut regions);
+            return regions;
+        }
+        _ => {}
+    }
 
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -23,50 +36,137 @@ pub fn render_ui(f: &mut Frame, app: &App, module_manager: &ModuleManager) {
         ])
         .split(size);
 
-    let left_content = render_section(module_manager, ModulePosition::Left);
-    let left = Paragraph::new(left_content).style(Style::default().bg(SURFACE));
-    f.render_widget(left, chunks[0]);
-
     let center_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(chunks[1]);
 
-    let center_content = render_section(module_manager, ModulePosition::Center);
-    let center = Paragraph::new(center_content)
-        .alignment(Alignment::Center)
-        .style(Style::default().bg(SURFACE));
-    f.render_widget(center, chunks[1]);
+    let sections = [
+        (ModulePosition::Left, Alignment::Left, chunks[0]),
+        (ModulePosition::Center, Alignment::Center, chunks[1]),
+        (
+            ModulePosition::CenterLeft,
+            Alignment::Left,
+            center_chunks[0],
+        ),
+        (
+            ModulePosition::CenterRight,
+            Alignment::Right,
+            center_chunks[1],
+        ),
+        (ModulePosition::Right, Alignment::Right, chunks[2]),
+    ];
 
-    let center_left_content = render_section(module_manager, ModulePosition::CenterLeft);
-    let center_left = Paragraph::new(center_left_content).style(Style::default().bg(SURFACE));
-    f.render_widget(center_left, center_chunks[0]);
+    for (position, alignment, area) in sections {
+        let (line, offsets) = render_section(module_manager, position);
+        draw_line(f, line, offsets, alignment, area, &mut regions);
+    }
 
-    let center_right_content = render_section(module_manager, ModulePosition::CenterRight);
-    let center_right = Paragraph::new(center_right_content)
-        .alignment(Alignment::Right)
-        .style(Style::default().bg(SURFACE));
-    f.render_widget(center_right, center_chunks[1]);
+    render_tooltip(f, module_manager);
+    regions
+}
+
+// Boxed below the bar, lined up with its module where it fits
+fn render_tooltip(f: &mut Frame, module_manager: &ModuleManager) {
+    let size = f.size();
+    if size.height < 2 {
+        return;
+    }
+    let Some((x, text)) = module_manager.tooltip() else {
+        return;
+    };
+
+    let width = (text.width() as u16 + 2).min(size.width);
+    let height = (text.height() as u16 + 2).min(size.height - 1);
+    let area = Rect {
+        x: x.min(size.width - width),
+        y: 1,
+        width,
+        height,
+    };
+
+    let paragraph = Paragraph::new(text).style(tooltip_style()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(tooltip_border_style()),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn render_minimal(
+    f: &mut Frame,
+    app: &App,
+    module_manager: &ModuleManager,
+    regions: &mut Vec<ModuleRegion>,
+) {
+    let (line, offsets) = match module_manager.get_module(app.minimal_module()) {
+        Some((index, module)) => {
+            let line = module.render();
+            let width = line.width() as u16;
+            (line, vec![(index, 0, width)])
+        }
+        None => (Line::default(), Vec::new()),
+    };
+    draw_line(f, line, offsets, Alignment::Center, f.size(), regions);
+}
+
+fn draw_line(
+    f: &mut Frame,
+    line: Line,
+    offsets: Vec<(usize, u16, u16)>,
+    alignment: Alignment,
+    area: Rect,
+    regions: &mut Vec<ModuleRegion>,
+) {
+    // same offsets Paragraph uses when it aligns a single line
+    let free = area.width.saturating_sub(line.width() as u16);
+    let start = area.x
+        + match alignment {
+            Alignment::Left => 0,
+            Alignment::Center => free / 2,
+            Alignment::Right => free,
+        };
 
-    let right_content = render_section(module_manager, ModulePosition::Right);
-    let right = Paragraph::new(right_content)
-        .alignment(Alignment::Right)
+    regions.extend(offsets.into_iter().map(|(index, x, width)| ModuleRegion {
+        index,
+        x: start + x,
+        width,
+    }));
+
+    let paragraph = Paragraph::new(line)
+        .alignment(alignment)
         .style(Style::default().bg(SURFACE));
-    f.render_widget(right, chunks[2]);
+    f.render_widget(paragraph, area);
 }
 
-fn render_section(module_manager: &ModuleManager, position: ModulePosition) -> Line {
+// Returns the section's line plus (module index, x offset, width) per module
+fn render_section(
+    module_manager: &ModuleManager,
+    position: ModulePosition,
+) -> (Line<'_>, Vec<(usize, u16, u16)>) {
     let modules = module_manager.get_modules_for_position(position);
 
     let mut spans = vec![Span::raw(" ")];
+    let mut offsets = Vec::new();
+    let mut x = 1;
 
-    for (i, module) in modules.iter().enumerate() {
-        spans.push(module.render());
+    for (index, module) in modules.iter() {
+        let line = module.render();
+        let width = line.width() as u16;
+        // a module with nothing to show gets no separator or region either
+        if width == 0 {
+            continue;
+        }
 
-        if i < modules.len() - 1 {
+        if !offsets.is_empty() {
             spans.push(Span::raw(" | "));
+            x += 3;
         }
+        offsets.push((*index, x, width));
+        spans.extend(line.spans);
+        x += width;
     }
 
-    Line::from(spans)
+    (Line::from(spans), offsets)
 }
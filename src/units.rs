@@ -0,0 +1,92 @@
+// Human-readable byte counts, shared by the modules that show sizes
+use serde::{Deserialize, Serialize};
+
+const IEC_UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+const SI_UNITS: [&str; 6] = ["B", "kB", "MB", "GB", "TB", "PB"];
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub enum UnitSystem {
+    // powers of 1000: kB, MB, GB
+    Si,
+    // powers of 1024: KiB, MiB, GiB
+    #[default]
+    Iec,
+}
+
+pub fn format_size(bytes: f64, system: UnitSystem, precision: usize) -> String {
+    let (base, units) = match system {
+        UnitSystem::Si => (1000.0, &SI_UNITS),
+        UnitSystem::Iec => (1024.0, &IEC_UNITS),
+    };
+    let mut value = bytes;
+    let mut unit = 0;
+
+    while value >= base && unit < units.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{:.0} {}", value, units[unit])
+    } else {
+        format!("{:.*} {}", precision, value, units[unit])
+    }
+}
+
+pub fn format_bytes(bytes: f64) -> String {
+    format_size(bytes, UnitSystem::Iec, 1)
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub enum RateUnit {
+    // KB/s, MB/s, GB/s in powers of 1024
+    #[default]
+    Bytes,
+    // kb/s, Mb/s, Gb/s in powers of 1000, like link speeds are quoted
+    Bits,
+}
+
+pub fn format_rate(bytes_per_sec: f64, unit: RateUnit) -> String {
+    let (mut value, base, units) = match unit {
+        RateUnit::Bytes => (bytes_per_sec / 1024.0, 1024.0, ["KB/s", "MB/s", "GB/s"]),
+        RateUnit::Bits => (
+            bytes_per_sec * 8.0 / 1000.0,
+            1000.0,
+            ["kb/s", "Mb/s", "Gb/s"],
+        ),
+    };
+    let mut scale = 0;
+
+    while value >= base && scale < units.len() - 1 {
+        value /= base;
+        scale += 1;
+    }
+
+    if scale == 0 {
+        format!("{:.0} {}", value, units[scale])
+    } else {
+        format!("{:.1} {}", value, units[scale])
+    }
+}
+
+pub fn format_speed(bytes_per_sec: f64) -> String {
+    format_rate(bytes_per_sec, RateUnit::Bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_rates_use_si_prefixes() {
+        assert_eq!(format_rate(125.0, RateUnit::Bits), "1 kb/s");
+        assert_eq!(format_rate(1_250_000.0, RateUnit::Bits), "10.0 Mb/s");
+        assert_eq!(format_rate(250_000_000.0, RateUnit::Bits), "2.0 Gb/s");
+    }
+
+    #[test]
+    fn byte_rates_use_powers_of_1024() {
+        assert_eq!(format_rate(2048.0, RateUnit::Bytes), "2 KB/s");
+        assert_eq!(format_rate(1536.0 * 1024.0, RateUnit::Bytes), "1.5 MB/s");
+    }
+}